@@ -12,7 +12,7 @@ use sp_std::prelude::*;
 use sp_core::OpaqueMetadata;
 use sp_runtime::{
 	ApplyExtrinsicResult, transaction_validity::TransactionValidity, generic, create_runtime_str,
-	impl_opaque_keys, MultiSignature
+	impl_opaque_keys, MultiSignature, ModuleId
 };
 use sp_runtime::traits::{
 	NumberFor, BlakeTwo256, Block as BlockT, StaticLookup, Verify, ConvertInto, IdentifyAccount
@@ -30,7 +30,7 @@ use sp_version::NativeVersion;
 pub use sp_runtime::BuildStorage;
 pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
-pub use sp_runtime::{Permill, Perbill};
+pub use sp_runtime::{Permill, Perbill, Percent};
 pub use frame_support::{
 	StorageValue, construct_runtime, parameter_types,
 	traits::Randomness,
@@ -63,6 +63,12 @@ pub type Hash = sp_core::H256;
 /// Digest item type.
 pub type DigestItem = generic::DigestItem<Hash>;
 
+/// Identifier of a listing in the TCR. This is a registry of integers.
+pub type ListingId = u32;
+
+/// Identifier of a listing in the token TCR: the hash of the listing data, such as a token contract.
+pub type TokenListingId = Hash;
+
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -233,19 +239,87 @@ impl sudo::Trait for Runtime {
 }
 
 parameter_types! {
-	pub const MinDeposit: Balance = 100;
-	pub const ApplyStageLen: BlockNumber = 10;
-	pub const CommitStageLen: BlockNumber = 10;
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 1_000;
+	pub const SpendPeriod: BlockNumber = DAYS;
+	pub const Burn: Permill = Permill::from_percent(0); // Unspent funds stay in the treasury
+}
+
+impl treasury::Trait for Runtime {
+	type Currency = Balances;
+	type ApproveOrigin = system::EnsureRoot<AccountId>; // Proposals are approved and rejected through sudo
+	type RejectOrigin = system::EnsureRoot<AccountId>;
+	type Event = Event;
+	type ProposalRejection = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+}
+
+parameter_types! {
+	pub const TcrModuleId: ModuleId = ModuleId(*b"py/tcreg");
+	pub const TokenTcrModuleId: ModuleId = ModuleId(*b"py/tcrtk");
+	pub const DispensationPct: Percent = Percent::from_percent(50);
+	pub const VoteQuorum: Balance = 100;
+	pub const MinVoteStake: Balance = 10;
+	pub const PassThreshold: Perbill = Perbill::from_percent(50);
+	pub const ExitDelay: BlockNumber = 10;
+	pub const MaxListingDataLen: u32 = 1024;
+	pub const OutcomeRetention: BlockNumber = 30 * DAYS;
+	pub const SettlementBudget: Weight = 250_000; // A quarter of the maximum block weight
+	pub const UpdateTip: Balance = 10;
+	pub const TipShare: Percent = Percent::from_percent(10);
+	pub const RegistryDeposit: Balance = 10_000;
+	pub const MinDepositFloor: Balance = 100;
+	pub const MinStageLen: BlockNumber = 10;
 }
 
 impl tcr::Trait for Runtime {
 	type Event = Event;
 	type Currency = Balances;
-	type ListingId = u32; // This is a registry of integers
+	type ListingId = ListingId;
+	type ParamOrigin = system::EnsureRoot<AccountId>; // Parameters are changed through sudo
+	type ModuleId = TcrModuleId;
+
+	type DispensationPct = DispensationPct;
+	type VoteQuorum = VoteQuorum;
+	type MinVoteStake = MinVoteStake;
+	type PassThreshold = PassThreshold;
+	type ExitDelay = ExitDelay;
+	type MaxListingDataLen = MaxListingDataLen;
+	type ListingIds = (); // Proposers pick their own integers
+	type OutcomeRetention = OutcomeRetention;
+	type SettlementBudget = SettlementBudget;
+	type UpdateTip = UpdateTip;
+	type RegistryDeposit = RegistryDeposit;
+	type MinDepositFloor = MinDepositFloor;
+	type MinStageLen = MinStageLen;
+	type Slash = tcr::SplitToPot<Runtime, TipShare, Treasury>; // Losing voters' stakes fund the tips and the treasury
+}
 
-	type MinDeposit = MinDeposit;
-	type ApplyStageLen = ApplyStageLen;
-	type CommitStageLen = CommitStageLen;
+// A second registry, of token contracts, with its own parameters set in the chain spec.
+impl tcr::Trait<tcr::Instance1> for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type ListingId = TokenListingId;
+	type ParamOrigin = system::EnsureRoot<AccountId>;
+	type ModuleId = TokenTcrModuleId;
+
+	type DispensationPct = DispensationPct;
+	type VoteQuorum = VoteQuorum;
+	type MinVoteStake = MinVoteStake;
+	type PassThreshold = PassThreshold;
+	type ExitDelay = ExitDelay;
+	type MaxListingDataLen = MaxListingDataLen;
+	type ListingIds = tcr::ContentAddressed; // Listing ids are the hash of the listing data
+	type OutcomeRetention = OutcomeRetention;
+	type SettlementBudget = SettlementBudget;
+	type UpdateTip = UpdateTip;
+	type RegistryDeposit = RegistryDeposit;
+	type MinDepositFloor = MinDepositFloor;
+	type MinStageLen = MinStageLen;
+	type Slash = tcr::SplitToPot<Runtime, TipShare, Treasury, tcr::Instance1>;
 }
 
 construct_runtime!(
@@ -262,8 +336,10 @@ construct_runtime!(
 		Balances: balances,
 		TransactionPayment: transaction_payment::{Module, Storage},
 		Sudo: sudo,
+		Treasury: treasury::{Module, Call, Storage, Event<T>},
 		RandomnessCollectiveFlip: randomness_collective_flip::{Module, Call, Storage},
-		Tcr: tcr::{Module, Call, Event<T>, Storage}
+		Tcr: tcr::{Module, Call, Event<T>, Storage, Config<T>},
+		TokenTcr: tcr::<Instance1>::{Module, Call, Event<T>, Storage, Config<T>},
 	}
 );
 
@@ -372,4 +448,130 @@ impl_runtime_apis! {
 			Grandpa::grandpa_authorities()
 		}
 	}
+
+	impl tcr_primitives::TcrApi<
+		Block,
+		tcr::RegistryId,
+		ListingId,
+		tcr::ChallengeId,
+		BlockNumber,
+		tcr::ListingDetailOf<Runtime>,
+		tcr::ChallengeDetailOf<Runtime>,
+	> for Runtime {
+		fn registry_contains(registry_id: tcr::RegistryId, listing_id: ListingId) -> bool {
+			Tcr::registry_contains(registry_id, listing_id)
+		}
+
+		fn listing(registry_id: tcr::RegistryId, listing_id: ListingId) -> Option<tcr::ListingDetailOf<Runtime>> {
+			Tcr::listing(registry_id, listing_id)
+		}
+
+		fn registry(
+			registry_id: tcr::RegistryId,
+			page: u32,
+			limit: u32,
+		) -> Vec<(ListingId, tcr::ListingDetailOf<Runtime>)> {
+			Tcr::registry(registry_id, page, limit)
+		}
+
+		fn applications(
+			registry_id: tcr::RegistryId,
+			page: u32,
+			limit: u32,
+		) -> Vec<(ListingId, tcr::ListingDetailOf<Runtime>)> {
+			Tcr::applications(registry_id, page, limit)
+		}
+
+		fn challenge(challenge_id: tcr::ChallengeId) -> Option<tcr::ChallengeDetailOf<Runtime>> {
+			Tcr::challenge(challenge_id)
+		}
+
+		fn pending_applications(registry_id: tcr::RegistryId) -> Vec<ListingId> {
+			Tcr::pending_applications(registry_id)
+		}
+
+		fn open_challenges() -> Vec<tcr::ChallengeId> {
+			Tcr::open_challenges()
+		}
+
+		fn upcoming_deadlines(
+			from: BlockNumber,
+			to: BlockNumber,
+		) -> Vec<(BlockNumber, tcr::RegistryId, ListingId)> {
+			Tcr::upcoming_deadlines(from, to)
+		}
+	}
+
+	impl tcr_primitives::TcrInstance1Api<
+		Block,
+		tcr::RegistryId,
+		TokenListingId,
+		tcr::ChallengeId,
+		BlockNumber,
+		tcr::ListingDetailOf<Runtime, tcr::Instance1>,
+		tcr::ChallengeDetailOf<Runtime, tcr::Instance1>,
+	> for Runtime {
+		fn registry_contains(registry_id: tcr::RegistryId, listing_id: TokenListingId) -> bool {
+			TokenTcr::registry_contains(registry_id, listing_id)
+		}
+
+		fn listing(
+			registry_id: tcr::RegistryId,
+			listing_id: TokenListingId,
+		) -> Option<tcr::ListingDetailOf<Runtime, tcr::Instance1>> {
+			TokenTcr::listing(registry_id, listing_id)
+		}
+
+		fn registry(
+			registry_id: tcr::RegistryId,
+			page: u32,
+			limit: u32,
+		) -> Vec<(TokenListingId, tcr::ListingDetailOf<Runtime, tcr::Instance1>)> {
+			TokenTcr::registry(registry_id, page, limit)
+		}
+
+		fn applications(
+			registry_id: tcr::RegistryId,
+			page: u32,
+			limit: u32,
+		) -> Vec<(TokenListingId, tcr::ListingDetailOf<Runtime, tcr::Instance1>)> {
+			TokenTcr::applications(registry_id, page, limit)
+		}
+
+		fn challenge(challenge_id: tcr::ChallengeId) -> Option<tcr::ChallengeDetailOf<Runtime, tcr::Instance1>> {
+			TokenTcr::challenge(challenge_id)
+		}
+
+		fn pending_applications(registry_id: tcr::RegistryId) -> Vec<TokenListingId> {
+			TokenTcr::pending_applications(registry_id)
+		}
+
+		fn open_challenges() -> Vec<tcr::ChallengeId> {
+			TokenTcr::open_challenges()
+		}
+
+		fn upcoming_deadlines(
+			from: BlockNumber,
+			to: BlockNumber,
+		) -> Vec<(BlockNumber, tcr::RegistryId, TokenListingId)> {
+			TokenTcr::upcoming_deadlines(from, to)
+		}
+	}
+
+	#[cfg(feature = "runtime-benchmarks")]
+	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn dispatch_benchmark(
+			module: Vec<u8>,
+			extrinsic: Vec<u8>,
+			steps: Vec<u32>,
+			repeat: u32,
+		) -> Option<Vec<frame_benchmarking::BenchmarkResults>> {
+			use frame_benchmarking::Benchmarking;
+
+			match module.as_slice() {
+				b"tcr" | b"Tcr" => Tcr::run_benchmark(extrinsic, steps, repeat).ok(),
+				_ => None,
+			}
+		}
+	}
 }
@@ -1,7 +1,7 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, IndicesConfig, SystemConfig, WASM_BINARY, Signature,
+	SudoConfig, IndicesConfig, SystemConfig, TcrConfig, TokenTcrConfig, WASM_BINARY, Signature,
 };
 use sp_consensus_aura::sr25519::{AuthorityId as AuraId};
 use grandpa_primitives::{AuthorityId as GrandpaId};
@@ -139,5 +139,17 @@ fn testnet_genesis(initial_authorities: Vec<(AuraId, GrandpaId)>,
 		grandpa: Some(GrandpaConfig {
 			authorities: initial_authorities.iter().map(|x| (x.1.clone(), 1)).collect(),
 		}),
+		tcr: Some(TcrConfig {
+			min_deposit: 100,
+			apply_stage_len: 10,
+			commit_stage_len: 10,
+			reveal_stage_len: 10,
+		}),
+		tcr_Instance1: Some(TokenTcrConfig {
+			min_deposit: 1_000,
+			apply_stage_len: 100,
+			commit_stage_len: 50,
+			reveal_stage_len: 50,
+		}),
 	}
 }
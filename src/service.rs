@@ -25,6 +25,10 @@ construct_simple_protocol! {
 	pub struct NodeProtocol where Block = Block { }
 }
 
+/// The custom RPC methods of the node, on top of the default Substrate ones. Full nodes evaluate
+/// them on their own state; light clients fetch them, with proofs, from full nodes.
+pub type RpcExtension = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
+
 /// Starts a `ServiceBuilder` for a full service.
 ///
 /// Use this macro if you don't actually need the full service, but just the builder in order to
@@ -73,6 +77,17 @@ macro_rules! new_full_start {
 				import_setup = Some((grandpa_block_import, grandpa_link));
 
 				Ok(import_queue)
+			})?
+			.with_rpc_extensions(|client, _pool, _backend, _fetcher, _remote_blockchain|
+				-> Result<crate::service::RpcExtension, _>
+			{
+				let mut io = jsonrpc_core::IoHandler::default();
+				io.extend_with(tcr_rpc::TcrApi::to_delegate(tcr_rpc::Tcr::new(client.clone())));
+				io.extend_with(tcr_rpc::in_namespace(
+					"tokenTcr",
+					tcr_rpc::TcrApi::to_delegate(tcr_rpc::TcrInstance1::new(client)),
+				));
+				Ok(io)
 			})?;
 
 		(builder, import_setup, inherent_data_providers)
@@ -240,5 +255,23 @@ pub fn new_light<C: Send + Default + 'static>(config: Configuration<C, GenesisCo
 		.with_finality_proof_provider(|client, backend|
 			Ok(Arc::new(GrandpaFinalityProofProvider::new(backend, client)) as _)
 		)?
+		.with_rpc_extensions(|client, _pool, _backend, fetcher, remote_blockchain|
+			-> Result<RpcExtension, _>
+		{
+			let fetcher = fetcher
+				.ok_or_else(|| "Trying to start light RPC without active fetcher")?;
+			let remote_blockchain = remote_blockchain
+				.ok_or_else(|| "Trying to start light RPC without active remote blockchain")?;
+
+			let mut io = jsonrpc_core::IoHandler::default();
+			io.extend_with(tcr_rpc::TcrApi::to_delegate(
+				tcr_rpc::LightTcr::new(client.clone(), remote_blockchain.clone(), fetcher.clone()),
+			));
+			io.extend_with(tcr_rpc::in_namespace(
+				"tokenTcr",
+				tcr_rpc::TcrApi::to_delegate(tcr_rpc::LightTcrInstance1::new(client, remote_blockchain, fetcher)),
+			));
+			Ok(io)
+		})?
 		.build()
 }
@@ -0,0 +1,65 @@
+//! Primitives of the TCR module, shared by the runtime and the node.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+/// Declares the query API of one instance of the TCR module. A runtime can implement a runtime
+/// API only once, so each instance it queries needs an API of its own.
+macro_rules! decl_tcr_api {
+	($(#[$attr:meta])* $name:ident) => {
+		sp_api::decl_runtime_apis! {
+			$(#[$attr])*
+			pub trait $name<RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge> where
+				RegistryId: Codec,
+				ListingId: Codec,
+				ChallengeId: Codec,
+				BlockNumber: Codec,
+				Listing: Codec,
+				Challenge: Codec,
+			{
+				/// Whether the listing is currently in the registry.
+				fn registry_contains(registry_id: RegistryId, listing_id: ListingId) -> bool;
+
+				/// The listing or application with the given id in a registry, if any.
+				fn listing(registry_id: RegistryId, listing_id: ListingId) -> Option<Listing>;
+
+				/// A page of the listings currently in a registry, with `limit` listings per page.
+				/// Limits above the module's `MAX_PAGE_LIMIT` are lowered to it.
+				fn registry(registry_id: RegistryId, page: u32, limit: u32) -> Vec<(ListingId, Listing)>;
+
+				/// A page of the applications that have not been accepted into a registry yet, with
+				/// `limit` applications per page. Limits above the module's `MAX_PAGE_LIMIT` are
+				/// lowered to it.
+				fn applications(registry_id: RegistryId, page: u32, limit: u32) -> Vec<(ListingId, Listing)>;
+
+				/// The challenge with the given id, if any. Resolved challenges are included until all
+				/// their votes are claimed.
+				fn challenge(challenge_id: ChallengeId) -> Option<Challenge>;
+
+				/// Unchallenged applications to a registry waiting for their application stage to end.
+				fn pending_applications(registry_id: RegistryId) -> Vec<ListingId>;
+
+				/// Challenges that have not been resolved yet, in any registry.
+				fn open_challenges() -> Vec<ChallengeId>;
+
+				/// Status updates scheduled between the `from` and `to` blocks, inclusive, in any
+				/// registry: application expiries, challenge stage ends and exits.
+				/// Ranges wider than the module's `MAX_DEADLINE_RANGE` are cut short.
+				fn upcoming_deadlines(from: BlockNumber, to: BlockNumber) -> Vec<(BlockNumber, RegistryId, ListingId)>;
+			}
+		}
+	}
+}
+
+decl_tcr_api! {
+	/// Queries on the token curated registries of the default instance of the TCR module, so that
+	/// clients do not have to decode raw storage.
+	TcrApi
+}
+
+decl_tcr_api! {
+	/// Queries on the token curated registries of `Instance1` of the TCR module.
+	TcrInstance1Api
+}
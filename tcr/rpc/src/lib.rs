@@ -0,0 +1,385 @@
+//! RPC interface for the TCR module. The default instance is served under the `tcr` namespace;
+//! other instances are moved to namespaces of their own with [`in_namespace`].
+//!
+//! Full nodes answer with runtime calls on their own state; light clients fetch the same calls,
+//! with proofs, from full nodes.
+
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+use codec::{Codec, Decode, Encode};
+use futures::future::{ready, FutureExt, TryFutureExt};
+use jsonrpc_core::{Error, ErrorCode, Metadata, RemoteProcedure, futures::{Future, future::result}};
+use jsonrpc_derive::rpc;
+use sc_client::light::{blockchain::{future_header, RemoteBlockchain}, fetcher::{Fetcher, RemoteCallRequest}};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::{Error as ClientError, HeaderBackend};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use self::gen_client::Client as TcrClient;
+pub use tcr_primitives::{TcrApi as TcrRuntimeApi, TcrInstance1Api as TcrInstance1RuntimeApi};
+
+/// Error code returned when a call into the runtime fails.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Namespace of the methods declared by [`TcrApi`].
+const NAMESPACE: &str = "tcr";
+
+/// The result of a TCR RPC method, which light clients only have once the full node answers.
+pub type FutureResult<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// TCR RPC methods. Every method is evaluated at the given block, or the best block if none is given.
+#[rpc]
+pub trait TcrApi<BlockHash, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge> {
+	/// The listing or application with the given id in a registry, if any.
+	#[rpc(name = "tcr_listing")]
+	fn listing(
+		&self,
+		registry_id: RegistryId,
+		listing_id: ListingId,
+		at: Option<BlockHash>,
+	) -> FutureResult<Option<Listing>>;
+
+	/// Whether the listing is currently in the registry.
+	#[rpc(name = "tcr_isRegistered")]
+	fn is_registered(
+		&self,
+		registry_id: RegistryId,
+		listing_id: ListingId,
+		at: Option<BlockHash>,
+	) -> FutureResult<bool>;
+
+	/// The challenge with the given id, if any.
+	#[rpc(name = "tcr_challenge")]
+	fn challenge(&self, challenge_id: ChallengeId, at: Option<BlockHash>) -> FutureResult<Option<Challenge>>;
+
+	/// A page of the listings currently in a registry, with `limit` listings per page.
+	/// Limits above the module's `MAX_PAGE_LIMIT` are lowered to it.
+	#[rpc(name = "tcr_registry")]
+	fn registry(
+		&self,
+		registry_id: RegistryId,
+		page: u32,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> FutureResult<Vec<(ListingId, Listing)>>;
+
+	/// A page of the applications that have not been accepted into a registry yet.
+	/// Limits above the module's `MAX_PAGE_LIMIT` are lowered to it.
+	#[rpc(name = "tcr_applications")]
+	fn applications(
+		&self,
+		registry_id: RegistryId,
+		page: u32,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> FutureResult<Vec<(ListingId, Listing)>>;
+
+	/// Status updates scheduled between the `from` and `to` blocks, inclusive, in any registry.
+	/// The runtime only scans a bounded number of blocks from `from`; wider ranges are cut short.
+	#[rpc(name = "tcr_deadlines")]
+	fn deadlines(
+		&self,
+		from: BlockNumber,
+		to: BlockNumber,
+		at: Option<BlockHash>,
+	) -> FutureResult<Vec<(BlockNumber, RegistryId, ListingId)>>;
+}
+
+/// Implements the TCR RPC methods for one instance of the module on a full node, with the
+/// runtime API the runtime implements for that instance.
+macro_rules! impl_tcr_rpc {
+	($(#[$attr:meta])* $name:ident, $runtime_api:ident) => {
+		$(#[$attr])*
+		pub struct $name<C, Block> {
+			client: Arc<C>,
+			_marker: PhantomData<Block>,
+		}
+
+		impl<C, Block> $name<C, Block> {
+			/// Create a new RPC handler with the given client.
+			pub fn new(client: Arc<C>) -> Self {
+				$name { client, _marker: Default::default() }
+			}
+		}
+
+		impl<C, Block: BlockT> $name<C, Block> where C: HeaderBackend<Block> {
+			/// The block to evaluate a call at: the given one, or the best block.
+			fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+				BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+			}
+		}
+
+		impl<C, Block, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge>
+			TcrApi<<Block as BlockT>::Hash, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge>
+			for $name<C, Block>
+		where
+			Block: BlockT,
+			C: Send + Sync + 'static,
+			C: ProvideRuntimeApi,
+			C: HeaderBackend<Block>,
+			C::Api: $runtime_api<Block, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge>,
+			RegistryId: Codec + Send + 'static,
+			ListingId: Codec + Send + 'static,
+			ChallengeId: Codec + Send + 'static,
+			BlockNumber: Codec + Send + 'static,
+			Listing: Codec + Send + 'static,
+			Challenge: Codec + Send + 'static,
+		{
+			fn listing(
+				&self,
+				registry_id: RegistryId,
+				listing_id: ListingId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Option<Listing>> {
+				Box::new(result(self.client.runtime_api()
+					.listing(&self.block_id(at), registry_id, listing_id)
+					.map_err(runtime_error)))
+			}
+
+			fn is_registered(
+				&self,
+				registry_id: RegistryId,
+				listing_id: ListingId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<bool> {
+				Box::new(result(self.client.runtime_api()
+					.registry_contains(&self.block_id(at), registry_id, listing_id)
+					.map_err(runtime_error)))
+			}
+
+			fn challenge(
+				&self,
+				challenge_id: ChallengeId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Option<Challenge>> {
+				Box::new(result(self.client.runtime_api()
+					.challenge(&self.block_id(at), challenge_id)
+					.map_err(runtime_error)))
+			}
+
+			fn registry(
+				&self,
+				registry_id: RegistryId,
+				page: u32,
+				limit: u32,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(ListingId, Listing)>> {
+				Box::new(result(self.client.runtime_api()
+					.registry(&self.block_id(at), registry_id, page, limit)
+					.map_err(runtime_error)))
+			}
+
+			fn applications(
+				&self,
+				registry_id: RegistryId,
+				page: u32,
+				limit: u32,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(ListingId, Listing)>> {
+				Box::new(result(self.client.runtime_api()
+					.applications(&self.block_id(at), registry_id, page, limit)
+					.map_err(runtime_error)))
+			}
+
+			fn deadlines(
+				&self,
+				from: BlockNumber,
+				to: BlockNumber,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(BlockNumber, RegistryId, ListingId)>> {
+				Box::new(result(self.client.runtime_api()
+					.upcoming_deadlines(&self.block_id(at), from, to)
+					.map_err(runtime_error)))
+			}
+		}
+	}
+}
+
+/// Implements the TCR RPC methods for one instance of the module on a light client, by fetching
+/// calls into the runtime API with the given name from full nodes.
+macro_rules! impl_light_tcr_rpc {
+	($(#[$attr:meta])* $name:ident, $runtime_api:expr) => {
+		$(#[$attr])*
+		pub struct $name<C, F, Block: BlockT> {
+			client: Arc<C>,
+			remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+			fetcher: Arc<F>,
+		}
+
+		impl<C, F, Block: BlockT> $name<C, F, Block> {
+			/// Create a new RPC handler with the given client, remote blockchain and fetcher.
+			pub fn new(
+				client: Arc<C>,
+				remote_blockchain: Arc<dyn RemoteBlockchain<Block>>,
+				fetcher: Arc<F>,
+			) -> Self {
+				$name { client, remote_blockchain, fetcher }
+			}
+		}
+
+		impl<C, F, Block> $name<C, F, Block> where
+			Block: BlockT,
+			C: HeaderBackend<Block>,
+			F: Fetcher<Block> + 'static,
+		{
+			/// Fetches a call into the runtime API at the given block, or the best block.
+			fn call<R: Decode + Send + 'static>(
+				&self,
+				at: Option<<Block as BlockT>::Hash>,
+				function: &str,
+				call_data: Vec<u8>,
+			) -> FutureResult<R> {
+				let block = at.unwrap_or_else(|| self.client.info().best_hash);
+				let method = format!("{}_{}", $runtime_api, function);
+				remote_call(&*self.remote_blockchain, self.fetcher.clone(), block, method, call_data)
+			}
+		}
+
+		impl<C, F, Block, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge>
+			TcrApi<<Block as BlockT>::Hash, RegistryId, ListingId, ChallengeId, BlockNumber, Listing, Challenge>
+			for $name<C, F, Block>
+		where
+			Block: BlockT,
+			C: Send + Sync + 'static,
+			C: HeaderBackend<Block>,
+			F: Fetcher<Block> + 'static,
+			RegistryId: Codec + Send + 'static,
+			ListingId: Codec + Send + 'static,
+			ChallengeId: Codec + Send + 'static,
+			BlockNumber: Codec + Send + 'static,
+			Listing: Codec + Send + 'static,
+			Challenge: Codec + Send + 'static,
+		{
+			fn listing(
+				&self,
+				registry_id: RegistryId,
+				listing_id: ListingId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Option<Listing>> {
+				self.call(at, "listing", (registry_id, listing_id).encode())
+			}
+
+			fn is_registered(
+				&self,
+				registry_id: RegistryId,
+				listing_id: ListingId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<bool> {
+				self.call(at, "registry_contains", (registry_id, listing_id).encode())
+			}
+
+			fn challenge(
+				&self,
+				challenge_id: ChallengeId,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Option<Challenge>> {
+				self.call(at, "challenge", challenge_id.encode())
+			}
+
+			fn registry(
+				&self,
+				registry_id: RegistryId,
+				page: u32,
+				limit: u32,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(ListingId, Listing)>> {
+				self.call(at, "registry", (registry_id, page, limit).encode())
+			}
+
+			fn applications(
+				&self,
+				registry_id: RegistryId,
+				page: u32,
+				limit: u32,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(ListingId, Listing)>> {
+				self.call(at, "applications", (registry_id, page, limit).encode())
+			}
+
+			fn deadlines(
+				&self,
+				from: BlockNumber,
+				to: BlockNumber,
+				at: Option<<Block as BlockT>::Hash>,
+			) -> FutureResult<Vec<(BlockNumber, RegistryId, ListingId)>> {
+				self.call(at, "upcoming_deadlines", (from, to).encode())
+			}
+		}
+	}
+}
+
+impl_tcr_rpc! {
+	/// Implements the TCR RPC methods with the `TcrApi` runtime API.
+	Tcr, TcrRuntimeApi
+}
+
+impl_tcr_rpc! {
+	/// Implements the TCR RPC methods with the `TcrInstance1Api` runtime API.
+	TcrInstance1, TcrInstance1RuntimeApi
+}
+
+impl_light_tcr_rpc! {
+	/// Implements the TCR RPC methods on a light client, with the `TcrApi` runtime API.
+	LightTcr, "TcrApi"
+}
+
+impl_light_tcr_rpc! {
+	/// Implements the TCR RPC methods on a light client, with the `TcrInstance1Api` runtime API.
+	LightTcrInstance1, "TcrInstance1Api"
+}
+
+/// Fetches a runtime call at the given block from full nodes, and decodes its result. The call
+/// data holds the encoded arguments of the runtime API function, in order.
+fn remote_call<Block, F, R>(
+	remote_blockchain: &dyn RemoteBlockchain<Block>,
+	fetcher: Arc<F>,
+	block: <Block as BlockT>::Hash,
+	method: String,
+	call_data: Vec<u8>,
+) -> FutureResult<R>
+where
+	Block: BlockT,
+	F: Fetcher<Block> + 'static,
+	R: Decode + Send + 'static,
+{
+	let header = future_header(remote_blockchain, &*fetcher, BlockId::hash(block))
+		.and_then(move |header| ready(header.ok_or_else(|| ClientError::UnknownBlock(format!("{}", block)))));
+	let encoded = header
+		.and_then(move |header| fetcher.remote_call(RemoteCallRequest {
+			block,
+			header,
+			method,
+			call_data,
+			retry_count: None,
+		}))
+		.boxed()
+		.compat();
+	Box::new(encoded
+		.and_then(|encoded| R::decode(&mut &encoded[..])
+			.map_err(|err| ClientError::CallResultDecodeFailed("Unable to decode the TCR query result", err)))
+		.map_err(runtime_error))
+}
+
+/// Converts a failed runtime call into an RPC error.
+fn runtime_error(err: impl Debug) -> Error {
+	Error {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Unable to query the TCR.".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+/// Moves RPC methods out of the `tcr` namespace and into the given one, so that every instance
+/// of the module can be served by the same node, e.g. `tokenTcr_listing` next to `tcr_listing`.
+pub fn in_namespace<M: Metadata>(
+	namespace: &str,
+	methods: impl IntoIterator<Item = (String, RemoteProcedure<M>)>,
+) -> Vec<(String, RemoteProcedure<M>)> {
+	let prefix = format!("{}_", NAMESPACE);
+	methods.into_iter()
+		.map(|(name, method)| if name.starts_with(&prefix) {
+			(format!("{}_{}", namespace, &name[prefix.len()..]), method)
+		} else {
+			(name, method)
+		})
+		.collect()
+}
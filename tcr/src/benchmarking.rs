@@ -0,0 +1,297 @@
+//! Benchmarks for the TCR calls, and for settling challenges in `on_finalize`.
+//! The parameter setters are benchmarked with the root origin, as used by the node runtime.
+//! Calls that do not depend on the size of their input are benchmarked against the number of
+//! entries already in the maps they write: registries for `create_registry` and the setters,
+//! listings of the registry for the calls on a listing. `challenge` is benchmarked on an
+//! application and on a registered listing, and `exit` both when it schedules the exit and when
+//! it completes it right away.
+
+use super::*;
+use frame_benchmarking::{account, benchmarks};
+use sp_runtime::traits::{Bounded, OnFinalize};
+use system::RawOrigin;
+
+const SEED: u32 = 0;
+const MAX_VOTES: u32 = 1_000;
+const MAX_REGISTRIES: u32 = 100;
+const MAX_LISTINGS: u32 = 1_000;
+const MAX_OWED: u32 = 1_000;
+
+/// An account with enough funds for any deposit made in the benchmarks.
+fn funded_account<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+	let who = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 1_000_000.into());
+	who
+}
+
+/// The registry parameters used by the benchmarks.
+fn parameters<T: Trait>() -> RegistryParamsOf<T> {
+	RegistryParams {
+		min_deposit: BalanceOf::<T>::from(100),
+		apply_stage_len: T::BlockNumber::from(10),
+		commit_stage_len: T::BlockNumber::from(10),
+		reveal_stage_len: T::BlockNumber::from(10),
+	}
+}
+
+/// Creates a registry with the benchmark parameters.
+fn new_registry<T: Trait>() -> RegistryId {
+	let creator = funded_account::<T>("creator", 0);
+	Module::<T>::insert_registry(creator, Zero::zero(), parameters::<T>())
+}
+
+/// Creates `r` registries, and returns the last one.
+fn new_registries<T: Trait>(r: u32) -> RegistryId {
+	(0..r).map(|_| new_registry::<T>()).last().unwrap_or_default()
+}
+
+/// The id and data of the `index`th listing, with at least `len` bytes of data.
+/// Ids are derived from the data, whether the registry is content-addressed or not.
+fn listing<T: Trait>(index: u32, len: u32) -> (ListingIdOf<T>, Vec<u8>) {
+	let mut data = index.encode();
+	data.resize(data.len().max(len as usize), 0);
+	let hash = T::Hashing::hash(&data);
+	let listing_id = T::ListingIds::from_data_hash(hash)
+		.unwrap_or_else(|| ListingIdOf::<T>::decode(&mut hash.as_ref()).unwrap_or_default());
+	(listing_id, data)
+}
+
+/// Proposes the `index`th listing in a registry, and registers it if `register` is set.
+fn propose_listing<T: Trait>(owner: &T::AccountId, registry_id: RegistryId, index: u32, register: bool) -> Result<ListingIdOf<T>, &'static str> {
+	let (listing_id, data) = listing::<T>(index, 0);
+	let deposit = Module::<T>::min_deposit(registry_id);
+	Module::<T>::propose(RawOrigin::Signed(owner.clone()).into(), registry_id, Some(listing_id), deposit, data)?;
+	if register {
+		let mut listing = Module::<T>::listings(registry_id, listing_id);
+		Module::<T>::promote_application(registry_id, listing_id, &mut listing);
+	}
+	Ok(listing_id)
+}
+
+/// Proposes a listing in a new registry, and registers it if `register` is set.
+fn proposed_listing<T: Trait>(register: bool) -> Result<(T::AccountId, RegistryId, ListingIdOf<T>), &'static str> {
+	proposed_listing_among::<T>(register, 0)
+}
+
+/// Proposes a listing in a new registry that already holds `l` other registered listings, and
+/// registers it if `register` is set.
+fn proposed_listing_among<T: Trait>(register: bool, l: u32) -> Result<(T::AccountId, RegistryId, ListingIdOf<T>), &'static str> {
+	let registry_id = new_registry::<T>();
+	let owner = funded_account::<T>("owner", 0);
+	for index in 1..=l {
+		propose_listing::<T>(&owner, registry_id, index, true)?;
+	}
+	let listing_id = propose_listing::<T>(&owner, registry_id, 0, register)?;
+	Ok((owner, registry_id, listing_id))
+}
+
+/// The salt of vote `i`.
+fn salt(i: u32) -> [u8; 32] {
+	let mut salt = [0u8; 32];
+	salt[..4].copy_from_slice(&i.to_le_bytes());
+	salt
+}
+
+/// The commitment of a voter to a side in a challenge, with the salt of vote `i`.
+fn commitment<T: Trait>(voter: &T::AccountId, challenge_id: ChallengeId, aye_or_nay: bool, i: u32) -> T::Hash {
+	T::Hashing::hash_of(&(voter, challenge_id, aye_or_nay, salt(i)))
+}
+
+/// Proposes and challenges a listing, and commits `v` votes alternating between aye and nay.
+/// Vote `i` is committed by the `i`th voter, with the salt of vote `i`.
+fn challenged_listing<T: Trait>(v: u32) -> Result<(RegistryId, ListingIdOf<T>), &'static str> {
+	let (_, registry_id, listing_id) = proposed_listing::<T>(false)?;
+	let deposit = Module::<T>::min_deposit(registry_id);
+	let challenger = funded_account::<T>("challenger", 0);
+	Module::<T>::challenge(RawOrigin::Signed(challenger).into(), registry_id, listing_id, deposit)?;
+	let (challenge_id, _) = challenge_of::<T>(registry_id, listing_id);
+	let deposit = deposit.max(T::MinVoteStake::get());
+	for i in 0..v {
+		let voter = funded_account::<T>("voter", i);
+		let commitment = commitment::<T>(&voter, challenge_id, i % 2 == 0, i);
+		Module::<T>::commit_vote(RawOrigin::Signed(voter).into(), registry_id, listing_id, commitment, deposit)?;
+	}
+	Ok((registry_id, listing_id))
+}
+
+/// The challenge of a listing.
+fn challenge_of<T: Trait>(registry_id: RegistryId, listing_id: ListingIdOf<T>) -> (ChallengeId, ChallengeDetailOf<T>) {
+	let challenge_id = Module::<T>::listings(registry_id, listing_id).challenge_id.unwrap_or_default();
+	(challenge_id, Module::<T>::challenges(challenge_id))
+}
+
+/// Reveals the first `v` votes committed by `challenged_listing`.
+fn reveal_votes<T: Trait>(registry_id: RegistryId, listing_id: ListingIdOf<T>, v: u32) -> Result<(), &'static str> {
+	let (_, challenge) = challenge_of::<T>(registry_id, listing_id);
+	system::Module::<T>::set_block_number(challenge.commit_end + One::one());
+	for i in 0..v {
+		let voter = account("voter", i, SEED);
+		Module::<T>::reveal_vote(RawOrigin::Signed(voter).into(), registry_id, listing_id, i % 2 == 0, salt(i))?;
+	}
+	Ok(())
+}
+
+/// Settles the challenge of a listing, with its first `v` votes revealed.
+fn settled_challenge<T: Trait>(v: u32) -> Result<(ChallengeId, T::BlockNumber), &'static str> {
+	let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+	reveal_votes::<T>(registry_id, listing_id, v)?;
+	let (challenge_id, challenge) = challenge_of::<T>(registry_id, listing_id);
+	system::Module::<T>::set_block_number(challenge.reveal_end);
+	Module::<T>::on_finalize(challenge.reveal_end);
+	Ok((challenge_id, challenge.reveal_end))
+}
+
+benchmarks! {
+	_ {
+		// Number of votes in the challenge
+		let v in 1 .. MAX_VOTES => ();
+		// Number of registries already created
+		let r in 1 .. MAX_REGISTRIES => ();
+		// Number of other listings already in the registry
+		let l in 0 .. MAX_LISTINGS => ();
+	}
+
+	create_registry {
+		let r in ...;
+		new_registries::<T>(r);
+		let caller = funded_account::<T>("caller", 0);
+	}: _(RawOrigin::Signed(caller), parameters::<T>())
+
+	set_min_deposit {
+		let r in ...;
+		let registry_id = new_registries::<T>(r);
+	}: _(RawOrigin::Root, registry_id, BalanceOf::<T>::from(200))
+
+	set_stage_lengths {
+		let r in ...;
+		let registry_id = new_registries::<T>(r);
+		let length = T::BlockNumber::from(20);
+	}: _(RawOrigin::Root, registry_id, length, length, length)
+
+	propose {
+		let d in 0 .. T::MaxListingDataLen::get();
+		let registry_id = new_registry::<T>();
+		let caller = funded_account::<T>("caller", 0);
+		let (listing_id, data) = listing::<T>(0, d);
+	}: _(RawOrigin::Signed(caller), registry_id, Some(listing_id), Module::<T>::min_deposit(registry_id), data)
+
+	challenge_application {
+		let l in ...;
+		let (_, registry_id, listing_id) = proposed_listing_among::<T>(false, l)?;
+		let caller = funded_account::<T>("caller", 0);
+		let deposit = Module::<T>::min_deposit(registry_id);
+	}: challenge(RawOrigin::Signed(caller), registry_id, listing_id, deposit)
+
+	challenge_listing {
+		let l in ...;
+		let (_, registry_id, listing_id) = proposed_listing_among::<T>(true, l)?;
+		let caller = funded_account::<T>("caller", 0);
+		let deposit = Module::<T>::min_deposit(registry_id);
+	}: challenge(RawOrigin::Signed(caller), registry_id, listing_id, deposit)
+
+	commit_vote {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		let caller = funded_account::<T>("caller", 0);
+		let (challenge_id, _) = challenge_of::<T>(registry_id, listing_id);
+		let commitment = commitment::<T>(&caller, challenge_id, true, v);
+	}: _(RawOrigin::Signed(caller), registry_id, listing_id, commitment, Module::<T>::min_deposit(registry_id))
+
+	reveal_vote {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		reveal_votes::<T>(registry_id, listing_id, v - 1)?;
+		let caller = account("voter", v - 1, SEED);
+	}: _(RawOrigin::Signed(caller), registry_id, listing_id, (v - 1) % 2 == 0, salt(v - 1))
+
+	change_vote {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		let caller = account("voter", v - 1, SEED);
+		let (challenge_id, _) = challenge_of::<T>(registry_id, listing_id);
+		let commitment = commitment::<T>(&caller, challenge_id, (v - 1) % 2 != 0, v);
+	}: _(RawOrigin::Signed(caller), registry_id, listing_id, commitment)
+
+	withdraw_vote {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		let caller = account("voter", v - 1, SEED);
+	}: _(RawOrigin::Signed(caller), registry_id, listing_id)
+
+	deposit_more {
+		let l in ...;
+		let (owner, registry_id, listing_id) = proposed_listing_among::<T>(true, l)?;
+	}: _(RawOrigin::Signed(owner), registry_id, listing_id, BalanceOf::<T>::from(100))
+
+	withdraw_excess {
+		let l in ...;
+		let (owner, registry_id, listing_id) = proposed_listing_among::<T>(true, l)?;
+		let amount = BalanceOf::<T>::from(100);
+		Module::<T>::deposit_more(RawOrigin::Signed(owner.clone()).into(), registry_id, listing_id, amount)?;
+	}: _(RawOrigin::Signed(owner), registry_id, listing_id, amount)
+
+	exit {
+		let l in ...;
+		let (owner, registry_id, listing_id) = proposed_listing_among::<T>(true, l)?;
+	}: _(RawOrigin::Signed(owner), registry_id, listing_id)
+
+	// `exit` with a zero `ExitDelay`, which the node runtime does not use: the checks of `exit`
+	// are negligible next to completing the exit, which is measured directly.
+	exit_immediately {
+		let l in ...;
+		let (_, registry_id, listing_id) = proposed_listing_among::<T>(true, l)?;
+		let listing = Module::<T>::listings(registry_id, listing_id);
+	}: {
+		Module::<T>::complete_exit(registry_id, listing_id, &listing);
+	}
+
+	claim_reward {
+		let v in ...;
+		let (challenge_id, _) = settled_challenge::<T>(v)?;
+		let caller = account("voter", 0, SEED);
+	}: _(RawOrigin::Signed(caller), challenge_id)
+
+	sweep_vote {
+		let v in ...;
+		// The last vote is left unrevealed, so it is forfeited whatever the outcome
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		reveal_votes::<T>(registry_id, listing_id, v - 1)?;
+		let (challenge_id, challenge) = challenge_of::<T>(registry_id, listing_id);
+		system::Module::<T>::set_block_number(challenge.reveal_end);
+		Module::<T>::on_finalize(challenge.reveal_end);
+		let voter: T::AccountId = account("voter", v - 1, SEED);
+		let caller = funded_account::<T>("caller", 0);
+	}: _(RawOrigin::Signed(caller), challenge_id, voter)
+
+	claim_owed {
+		// Number of accounts owed by the pot
+		let o in 1 .. MAX_OWED;
+		let amount = BalanceOf::<T>::from(100);
+		for i in 1..o {
+			let who: T::AccountId = account("owed", i, SEED);
+			<Owed<T>>::insert(&who, amount);
+		}
+		let caller = funded_account::<T>("caller", 0);
+		<Owed<T>>::insert(&caller, amount);
+		let _ = T::Currency::deposit_creating(&Module::<T>::account_id(), amount);
+	}: _(RawOrigin::Signed(caller))
+
+	update_status {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		reveal_votes::<T>(registry_id, listing_id, v)?;
+		let (_, challenge) = challenge_of::<T>(registry_id, listing_id);
+		system::Module::<T>::set_block_number(challenge.reveal_end + One::one());
+		let caller = funded_account::<T>("caller", 0);
+	}: _(RawOrigin::Signed(caller), registry_id, listing_id)
+
+	on_finalize_settlement {
+		let v in ...;
+		let (registry_id, listing_id) = challenged_listing::<T>(v)?;
+		reveal_votes::<T>(registry_id, listing_id, v)?;
+		let (_, challenge) = challenge_of::<T>(registry_id, listing_id);
+		system::Module::<T>::set_block_number(challenge.reveal_end);
+	}: {
+		Module::<T>::on_finalize(challenge.reveal_end);
+	}
+}
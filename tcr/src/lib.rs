@@ -2,11 +2,17 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use sp_std::prelude::*;
-use sp_runtime::traits::CheckedAdd;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::{marker::PhantomData, prelude::*};
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational, ModuleId, Perbill, Percent,
+	traits::{AccountIdConversion, CheckedAdd, Hash, One, SaturatedConversion, Saturating, Zero},
+};
 use frame_support::{
-	decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, Parameter,
-	traits::{ Currency, ReservableCurrency, Get },
+	decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, Parameter,
+	traits::{ Contains, Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, OnUnbalanced, WithdrawReason },
+	weights::{SimpleDispatchInfo, Weight},
 };
 use system::ensure_signed;
 
@@ -16,28 +22,191 @@ use system::ensure_signed;
 #[cfg(test)]
 mod tests;
 
-// The module trait
-pub trait Trait: system::Trait {
-	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
-	type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+mod weights;
+
+// The module trait. Each instance of the module has its own registries, pot and listing ids.
+pub trait Trait<I: Instance = DefaultInstance>: system::Trait {
+	type Event: From<Event<Self, I>> + Into<<Self as system::Trait>::Event>;
+	type Currency: Currency<Self::AccountId>;
 	type ListingId: Parameter + Encode + Decode + Default + Copy;
-	// The TCR Parameters
-	type MinDeposit: Get<BalanceOf<Self>>;
-	type ApplyStageLen: Get<Self::BlockNumber>;
-	type CommitStageLen: Get<Self::BlockNumber>;
+	/// The origin allowed to change the parameters of any registry.
+	type ParamOrigin: EnsureOrigin<Self::Origin>;
+	/// The id of the TCR's pot, holding every deposit and stake, and reward pools until voters
+	/// claim them. Each instance needs its own.
+	type ModuleId: Get<ModuleId>;
+	// The TCR Parameters that are fixed at compile time
+	/// Share of the losing party's deposit awarded to the winning party. The rest goes to the winning voters.
+	type DispensationPct: Get<Percent>;
+	/// Minimum stake of revealed votes for a challenge to be decided. Otherwise the status quo wins.
+	type VoteQuorum: Get<BalanceOf<Self, I>>;
+	/// Minimum stake of a vote. Each vote is stored until it is claimed or swept, so it cannot be free.
+	type MinVoteStake: Get<BalanceOf<Self, I>>;
+	/// Share of the vote, including the parties' deposits, that the aye side needs to keep a listing.
+	type PassThreshold: Get<Perbill>;
+	/// Delay between an owner asking to exit and the listing leaving the registry.
+	/// The listing can still be challenged in the meantime. Zero means owners exit immediately.
+	type ExitDelay: Get<Self::BlockNumber>;
+	/// Maximum length in bytes of the data attached to a listing.
+	type MaxListingDataLen: Get<u32>;
+	/// Whether proposers pick listing ids, or ids are derived from the listing data.
+	type ListingIds: ListingIds<Self::Hash, Self::ListingId>;
+	/// Number of blocks for which the outcome of a resolved challenge is kept in `ChallengeOutcomes`.
+	/// Zero keeps outcomes forever.
+	type OutcomeRetention: Get<Self::BlockNumber>;
+	/// Maximum weight of the status updates processed in `on_finalize`. Updates over budget are
+	/// processed in the following blocks.
+	type SettlementBudget: Get<Weight>;
+	/// Tip paid from the pot's surplus to whoever calls `update_status` successfully.
+	type UpdateTip: Get<BalanceOf<Self, I>>;
+	/// Where the stakes forfeited by losing voters go, e.g. a treasury. `ToPot` keeps them as
+	/// the pot's surplus, which pays for tips, `SplitToPot` keeps part of them as surplus, and
+	/// `()` burns them.
+	type Slash: OnUnbalanced<NegativeImbalanceOf<Self, I>>;
+	/// Deposit staked by whoever creates a registry with `create_registry`. Registries are never
+	/// removed, so it stays in the pot.
+	type RegistryDeposit: Get<BalanceOf<Self, I>>;
+	/// Lowest minimum deposit a registry can have. Listings cannot be free, or the registries could
+	/// be flooded with them.
+	type MinDepositFloor: Get<BalanceOf<Self, I>>;
+	/// Shortest apply, commit or reveal stage a registry can have, so that there is always time to
+	/// challenge, vote and reveal.
+	type MinStageLen: Get<Self::BlockNumber>;
+}
+
+/// Decides how the id of a new listing is chosen.
+pub trait ListingIds<Hash, ListingId> {
+	/// The id of a listing whose data has the given hash, or `None` if proposers pick their own ids.
+	fn from_data_hash(hash: Hash) -> Option<ListingId>;
+}
+
+/// Proposers pick any unused listing id.
+impl<Hash, ListingId> ListingIds<Hash, ListingId> for () {
+	fn from_data_hash(_: Hash) -> Option<ListingId> {
+		None
+	}
+}
+
+/// Listing ids are the hash of the listing data, so proposals for the same content always
+/// collide, and clients can compute the id of some content offline.
+pub struct ContentAddressed;
+
+impl<Hash, ListingId: From<Hash>> ListingIds<Hash, ListingId> for ContentAddressed {
+	fn from_data_hash(hash: Hash) -> Option<ListingId> {
+		Some(hash.into())
+	}
+}
+
+/// Keeps slashed funds in the TCR's pot, as surplus paying for `update_status` tips.
+pub struct ToPot<T, I = DefaultInstance>(PhantomData<(T, I)>);
+
+impl<T: Trait<I>, I: Instance> OnUnbalanced<NegativeImbalanceOf<T, I>> for ToPot<T, I> {
+	fn on_unbalanced(amount: NegativeImbalanceOf<T, I>) {
+		<PotSurplus<T, I>>::mutate(|surplus| *surplus = surplus.saturating_add(amount.peek()));
+		T::Currency::resolve_creating(&<Module<T, I>>::account_id(), amount);
+	}
+}
+
+/// Keeps `Share` of slashed funds in the pot as `ToPot` does, so that `update_status` tips are
+/// funded, and passes the rest on to `Rest`, e.g. a treasury.
+pub struct SplitToPot<T, Share, Rest, I = DefaultInstance>(PhantomData<(T, Share, Rest, I)>);
+
+impl<T, Share, Rest, I> OnUnbalanced<NegativeImbalanceOf<T, I>> for SplitToPot<T, Share, Rest, I> where
+	T: Trait<I>,
+	Share: Get<Percent>,
+	Rest: OnUnbalanced<NegativeImbalanceOf<T, I>>,
+	I: Instance,
+{
+	fn on_unbalanced(amount: NegativeImbalanceOf<T, I>) {
+		let kept = Share::get() * amount.peek();
+		let (kept, rest) = amount.split(kept);
+		ToPot::<T, I>::on_unbalanced(kept);
+		Rest::on_unbalanced(rest);
+	}
+}
+
+/// Membership of the genesis registry, for TCRs listing accounts.
+impl<T: Trait<I, ListingId = AccountIdOf<T>>, I: Instance> Contains<AccountIdOf<T>> for Module<T, I> {
+	fn contains(who: &AccountIdOf<T>) -> bool {
+		Self::registry_contains(GENESIS_REGISTRY, who.clone())
+	}
+
+	fn sorted_members() -> Vec<AccountIdOf<T>> {
+		let mut members = (0..Self::registry_count(GENESIS_REGISTRY))
+			.map(|position| Self::registry_listing(GENESIS_REGISTRY, position))
+			.collect::<Vec<_>>();
+		members.sort();
+		members
+	}
+}
+
+/// Ensures the origin is signed by an account currently listed in the genesis registry, for
+/// TCRs listing accounts.
+pub struct EnsureRegistered<T, I = DefaultInstance>(PhantomData<(T, I)>);
+
+impl<O, T, I> EnsureOrigin<O> for EnsureRegistered<T, I> where
+	O: Into<Result<system::RawOrigin<AccountIdOf<T>>, O>> + From<system::RawOrigin<AccountIdOf<T>>>,
+	T: Trait<I, ListingId = AccountIdOf<T>>,
+	I: Instance,
+{
+	type Success = AccountIdOf<T>;
+
+	fn try_origin(o: O) -> Result<Self::Success, O> {
+		o.into().and_then(|o| match o {
+			system::RawOrigin::Signed(ref who) if <Module<T, I>>::contains(who) => Ok(who.clone()),
+			r => Err(O::from(r)),
+		})
+	}
 }
 
-type ChallengeId = u32;
-type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
-type AccountIdOf<T> = <T as system::Trait>::AccountId;
-type BlockNumberOf<T> = <T as system::Trait>::BlockNumber;
-type ListingIdOf<T> = <T as Trait>::ListingId;
+/// The registry created at genesis. It is the one other modules gate on through `Contains`
+/// and `EnsureRegistered`.
+pub const GENESIS_REGISTRY: RegistryId = 0;
+
+/// The widest range of blocks `upcoming_deadlines` scans in one call, so that a single query
+/// cannot make a node read an unbounded number of `ListingsToUpdate` entries.
+pub const MAX_DEADLINE_RANGE: u32 = 10_000;
+
+/// The most listings `registry` and `applications` return in one page. Larger limits are
+/// lowered to it, so that a single query reads a bounded number of listings.
+pub const MAX_PAGE_LIMIT: u32 = 100;
+
+pub type RegistryId = u32;
+pub type ChallengeId = u32;
+pub type BalanceOf<T, I = DefaultInstance> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+pub type NegativeImbalanceOf<T, I = DefaultInstance> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+pub type AccountIdOf<T> = <T as system::Trait>::AccountId;
+pub type BlockNumberOf<T> = <T as system::Trait>::BlockNumber;
+pub type ListingIdOf<T, I = DefaultInstance> = <T as Trait<I>>::ListingId;
+
+pub type RegistryDetailOf<T, I = DefaultInstance> = RegistryDetail<AccountIdOf<T>, BalanceOf<T, I>>;
+pub type RegistryParamsOf<T, I = DefaultInstance> = RegistryParams<BalanceOf<T, I>, BlockNumberOf<T>>;
+pub type ListingDetailOf<T, I = DefaultInstance> = ListingDetail<BalanceOf<T, I>, AccountIdOf<T>, BlockNumberOf<T>>;
+pub type ChallengeDetailOf<T, I = DefaultInstance> = ChallengeDetail<ListingIdOf<T, I>, BalanceOf<T, I>, AccountIdOf<T>, BlockNumberOf<T>>;
+pub type VoteOf<T, I = DefaultInstance> = Vote<BalanceOf<T, I>, <T as system::Trait>::Hash>;
+pub type ChallengeOutcomeOf<T, I = DefaultInstance> = ChallengeOutcome<ListingIdOf<T, I>, AccountIdOf<T>, BalanceOf<T, I>, BlockNumberOf<T>>;
+
+/// Who created a registry, and the deposit they staked for it.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RegistryDetail<AccountId, Balance> {
+	creator: AccountId,
+	deposit: Balance,
+}
 
-type ListingDetailOf<T> = ListingDetail<BalanceOf<T>, AccountIdOf<T>, BlockNumberOf<T>>;
-type ChallengeDetailOf<T> = ChallengeDetail<<T as Trait>::ListingId, BalanceOf<T>, AccountIdOf<T>, VoteOf<T>>;
-type VoteOf<T> = Vote<AccountIdOf<T>, BalanceOf<T>>;
+/// The parameters of a registry. They apply to listings and challenges opened after they are set.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct RegistryParams<Balance, BlockNumber> {
+	pub min_deposit: Balance, // The minimum deposit needed to propose a listing
+	pub apply_stage_len: BlockNumber, // Blocks during which a new application can be challenged before it is accepted
+	pub commit_stage_len: BlockNumber, // Blocks during which votes can be committed in a challenge
+	pub reveal_stage_len: BlockNumber, // Blocks after the commit stage during which votes can be revealed
+}
 
-#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 pub struct ListingDetail<Balance, AccountId, BlockNumber> {
 	deposit: Balance,
@@ -45,326 +214,1692 @@ pub struct ListingDetail<Balance, AccountId, BlockNumber> {
 	application_expiry: Option<BlockNumber>,
 	in_registry: bool,
 	challenge_id: Option<ChallengeId>,
+	exit_expiry: Option<BlockNumber>,
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct ChallengeDetail<ListingId, Balance, AccountId, Vote> {
+pub struct ChallengeDetail<ListingId, Balance, AccountId, BlockNumber> {
+	registry_id: RegistryId,
 	listing_id: ListingId,
 	deposit: Balance,
 	owner: AccountId,
 	total_aye: Balance,
 	total_nay: Balance,
-	votes: Vec<Vote>,
+	commit_end: BlockNumber, // Last block in which votes may be committed
+	reveal_end: BlockNumber, // Last block in which votes may be revealed. The challenge is settled at the end of it.
+	vote_count: u32, // Number of votes committed until resolved, then of the winning (or, without quorum, revealed) votes left to claim
+	committed_stake: Balance, // Total stake of the committed votes, revealed or not
+	aye_votes: u32, // Number of revealed aye votes
+	nay_votes: u32, // Number of revealed nay votes
+	outcome: Option<bool>, // None until resolved. true means the listing passed the challenge
+	reward_pool: Balance, // Rewards left to be claimed by the winning voters, held in the pot
+	winning_stake: Balance, // Total stake of the revealed votes on the winning side that are left to claim
+	quorum_met: bool, // Whether the vote quorum was reached. Only meaningful once resolved
+	application_expiry: Option<BlockNumber>, // End of the apply stage of a challenged application, which resumes if the challenge is voided
 }
 
-#[cfg_attr(feature = "std", derive(Debug))]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct Vote<AccountId, Balance> {
-	voter: AccountId,
-	aye_or_nay: bool, // true means: I want this item in the registry. false means: I do not want this item in the registry
+pub struct Vote<Balance, Hash> {
+	commitment: Hash, // hash of (voter, challenge_id, aye_or_nay, salt) submitted during the commit stage
+	aye_or_nay: Option<bool>, // None until revealed. true means: I want this item in the registry. false means: I do not want this item in the registry
 	deposit: Balance,
 }
 
+/// What is kept of a challenge once it is resolved and all its votes are claimed.
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct ChallengeOutcome<ListingId, AccountId, Balance, BlockNumber> {
+	registry_id: RegistryId,
+	listing_id: ListingId,
+	challenger: AccountId,
+	total_aye: Balance,
+	total_nay: Balance,
+	listing_kept: bool, // Whether the listing passed the challenge, or was kept for lack of quorum
+	quorum_met: bool,
+	resolved_at: BlockNumber,
+}
+
 decl_storage! {
-	trait Store for Module<T: Trait> as Tcr {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as Tcr {
+
+		/// The registries created so far, with their creator and creation deposit.
+		Registries get(registries): map RegistryId => RegistryDetailOf<T, I>;
 
-		/// All listings and applicants known to the TCR. Inclusion in this map is NOT the same as listing in the registry,
+		/// The first unused registry Id. Will become the Id of the next registry when it is created.
+		NextRegistryId get(next_registry_id): RegistryId;
+
+		// The parameters of each registry. These can be changed by `T::ParamOrigin`, and the changes
+		// apply to listings and challenges opened afterwards.
+
+		/// The minimum deposit needed to propose a listing.
+		MinDeposit get(min_deposit): map RegistryId => BalanceOf<T, I>;
+
+		/// Number of blocks during which a new application can be challenged before it is accepted.
+		ApplyStageLen get(apply_stage_len): map RegistryId => T::BlockNumber;
+
+		/// Number of blocks during which votes can be committed in a challenge.
+		CommitStageLen get(commit_stage_len): map RegistryId => T::BlockNumber;
+
+		/// Number of blocks after the commit stage during which votes can be revealed.
+		RevealStageLen get(reveal_stage_len): map RegistryId => T::BlockNumber;
+
+		/// All listings and applicants known to each registry. Inclusion in this map is NOT the same as listing in the registry,
 		/// because this map also includes new applicants (some of which are challenged)
-		Listings get(listings): map hasher(blake2_256) T::ListingId => ListingDetailOf<T>;
+		Listings get(listings): double_map hasher(blake2_256) RegistryId, blake2_256(T::ListingId) => ListingDetailOf<T, I>;
+
+		/// The data attached to each listing when it was proposed, such as a URI, a content hash or a JSON blob.
+		/// Kept apart from `Listings` so that the data is only decoded when needed.
+		ListingData get(listing_data): double_map hasher(blake2_256) RegistryId, blake2_256(T::ListingId) => Vec<u8>;
+
+		/// The listings currently in each registry, by position, so that the registry can be paged through.
+		/// Positions are dense: removing a listing moves the last one into its place.
+		RegistryListings get(registry_listing): double_map hasher(blake2_256) RegistryId, blake2_256(u32) => T::ListingId;
+
+		/// The position of each registered listing in `RegistryListings`.
+		RegistryIndex: double_map hasher(blake2_256) RegistryId, blake2_256(T::ListingId) => u32;
+
+		/// Number of listings currently in each registry.
+		RegistryCount get(registry_count): map RegistryId => u32;
+
+		/// The applications that have not been accepted into each registry yet, challenged or not, by position.
+		/// Positions are dense: removing an application moves the last one into its place.
+		Applications get(application): double_map hasher(blake2_256) RegistryId, blake2_256(u32) => T::ListingId;
+
+		/// The position of each application in `Applications`.
+		ApplicationsIndex: double_map hasher(blake2_256) RegistryId, blake2_256(T::ListingId) => u32;
+
+		/// Number of applications that have not been accepted into each registry yet.
+		ApplicationsCount get(applications_count): map RegistryId => u32;
 
-		/// All currently open challenges
-		Challenges get(challenges): map ChallengeId => ChallengeDetailOf<T>;
+		/// All open challenges, and resolved challenges until all their winning or undecided votes are
+		/// claimed. Challenge ids are shared by all registries, and each challenge records the registry
+		/// of its listing.
+		Challenges get(challenges): map ChallengeId => ChallengeDetailOf<T, I>;
+
+		/// The outcomes of resolved challenges, kept for `T::OutcomeRetention` blocks after resolution.
+		ChallengeOutcomes get(challenge_outcomes): map ChallengeId => ChallengeOutcomeOf<T, I>;
+
+		/// The challenge outcomes to drop from `ChallengeOutcomes` at the end of each block.
+		OutcomesToPrune get(outcomes_to_prune): map BlockNumberOf<T> => Vec<ChallengeId>;
 
 		/// The first unused challenge Id. Will become the Id of the next challenge when it is open.
 		NextChallengeId get(next_challenge_id): ChallengeId;
 
+		/// The challenges that have not been resolved yet, in any registry, by position.
+		/// Positions are dense: removing a challenge moves the last one into its place.
+		OpenChallenges get(open_challenge): map u32 => ChallengeId;
+
+		/// The position of each unresolved challenge in `OpenChallenges`.
+		OpenChallengesIndex: map ChallengeId => u32;
+
+		/// Number of challenges that have not been resolved yet.
+		OpenChallengesCount get(open_challenges_count): u32;
+
 		/// Mapping from the blocknumber when a listing may need a status update to the Listing Id
 		/// tht may need the update. This is used to automatically resolve challenges and promote
 		/// unchallenged listings in `on_finalize`. Not all entries in this map will actually need
 		/// an update. For example, an application that has been challenged will not actually be
-		/// updated at its original application expiry.
-		ListingsToUpdate get(challenge_expiry): map BlockNumberOf<T> => Vec<T::ListingId>;
+		/// updated at its original application expiry. The listings of all registries are scheduled
+		/// here, so that `on_finalize` does not have to go through every registry.
+		ListingsToUpdate get(challenge_expiry): map BlockNumberOf<T> => Vec<(RegistryId, T::ListingId)>;
+
+		/// The first status update that did not fit in the settlement budget: the block it was scheduled
+		/// for, and its position in that block's `ListingsToUpdate`. `None` when there is no backlog.
+		UpdateCursor get(update_cursor): Option<(BlockNumberOf<T>, u32)>;
+
+		/// The part of the pot's balance that is neither staked nor owed to winning voters: the
+		/// slashed funds kept by `ToPot`, less the `update_status` tips paid out of it.
+		PotSurplus get(pot_surplus): BalanceOf<T, I>;
+
+		/// Weight used by the status updates in `on_finalize` of the last block. `on_finalize` cannot
+		/// report its weight to the system module, so it is recorded here.
+		FinalizeWeight get(finalize_weight): Weight;
+
+		/// The votes committed in each challenge, by voter. Each account votes at most once in a challenge.
+		/// Votes are removed when claimed, or when swept after being forfeited.
+		Votes get(votes): double_map hasher(blake2_256) ChallengeId, blake2_256(T::AccountId) => VoteOf<T, I>;
+
+		/// Whether a voter has already claimed their stake and reward from a resolved challenge, or had
+		/// their forfeited vote swept.
+		Claimed get(claimed): double_map hasher(blake2_256) ChallengeId, blake2_256(T::AccountId) => bool;
+
+		// Per-account indexes of what each account has at stake. Positions are dense, like those
+		// of `RegistryListings`.
+
+		/// The listings and applications owned by each account in any registry, by position.
+		OwnedListings get(owned_listing): double_map hasher(blake2_256) T::AccountId, blake2_256(u32) => (RegistryId, T::ListingId);
+
+		/// Number of listings and applications owned by each account.
+		OwnedListingsCount get(owned_listings_count): map hasher(blake2_256) T::AccountId => u32;
+
+		/// The position of each listing in its owner's `OwnedListings`.
+		OwnedListingsIndex: double_map hasher(blake2_256) RegistryId, blake2_256(T::ListingId) => u32;
+
+		/// The unresolved challenges opened by each account, by position.
+		OpenedChallenges get(opened_challenge): double_map hasher(blake2_256) T::AccountId, blake2_256(u32) => ChallengeId;
+
+		/// Number of unresolved challenges opened by each account.
+		OpenedChallengesCount get(opened_challenges_count): map hasher(blake2_256) T::AccountId => u32;
+
+		/// The position of each unresolved challenge in its challenger's `OpenedChallenges`.
+		OpenedChallengesIndex: map ChallengeId => u32;
+
+		/// The challenges each account has voted in and not claimed from yet, by position.
+		CastVotes get(cast_vote): double_map hasher(blake2_256) T::AccountId, blake2_256(u32) => ChallengeId;
+
+		/// Number of unclaimed votes cast by each account.
+		CastVotesCount get(cast_votes_count): map hasher(blake2_256) T::AccountId => u32;
+
+		/// The position of each unclaimed vote in its voter's `CastVotes`.
+		CastVotesIndex: double_map hasher(blake2_256) ChallengeId, blake2_256(T::AccountId) => u32;
+
+		/// The total of each account's registry deposits, listing deposits, challenge deposits and
+		/// vote stakes currently held in the pot. Forfeited vote stakes leave the pot when their
+		/// challenge is resolved, but are only taken off here when the vote is claimed or swept.
+		TotalStaked get(total_staked): map hasher(blake2_256) T::AccountId => BalanceOf<T, I>;
+
+		/// What the pot owes each account after payments out of it failed while resolving listings.
+		/// Claimed with `claim_owed`.
+		Owed get(owed): map hasher(blake2_256) T::AccountId => BalanceOf<T, I>;
+	}
+	add_extra_genesis {
+		config(min_deposit): BalanceOf<T, I>;
+		config(apply_stage_len): T::BlockNumber;
+		config(commit_stage_len): T::BlockNumber;
+		config(reveal_stage_len): T::BlockNumber;
+		build(|config: &GenesisConfig<T, I>| {
+			// Create the pot, so that it can take deposits below the existential deposit
+			let pot = <Module<T, I>>::account_id();
+			let _ = T::Currency::make_free_balance_be(&pot, T::Currency::minimum_balance());
+
+			// Create the first registry, held by the pot itself without a deposit
+			<Module<T, I>>::check_min_deposit(config.min_deposit)
+				.and_then(|_| <Module<T, I>>::check_stage_lengths(config.apply_stage_len, config.commit_stage_len, config.reveal_stage_len))
+				.expect("the parameters of the genesis registry are below the lower bounds of the runtime");
+			<Module<T, I>>::insert_registry(pot, Zero::zero(), RegistryParams {
+				min_deposit: config.min_deposit,
+				apply_stage_len: config.apply_stage_len,
+				commit_stage_len: config.commit_stage_len,
+				reveal_stage_len: config.reveal_stage_len,
+			});
+		});
 	}
 }
 
 // Events
 decl_event!(
-	pub enum Event<T>
+	pub enum Event<T, I = DefaultInstance>
 		where AccountId = <T as system::Trait>::AccountId,
-		Balance = BalanceOf<T>,
-		ListingId = ListingIdOf<T>,
+		Balance = BalanceOf<T, I>,
+		BlockNumber = BlockNumberOf<T>,
+		ListingId = ListingIdOf<T, I>,
 	{
-		/// A user has proposed a new listing
-		Proposed(AccountId, ListingId, Balance),
+		/// A user has created a registry, staking the given deposit
+		RegistryCreated(AccountId, RegistryId, Balance),
+
+		/// The minimum deposit of a registry has been changed
+		MinDepositChanged(RegistryId, Balance),
+
+		/// The apply, commit and reveal stage lengths of a registry have been changed
+		StageLengthsChanged(RegistryId, BlockNumber, BlockNumber, BlockNumber),
+
+		/// A user has proposed a new listing, with the data attached to it
+		Proposed(AccountId, RegistryId, ListingId, Balance, Vec<u8>),
 
 		/// A user has challenged a listing. The challenged listing may be already listed,
 		/// or an applicant
-		Challenged(AccountId, ListingId, ChallengeId, Balance),
+		Challenged(AccountId, RegistryId, ListingId, ChallengeId, Balance),
 
-		/// A user cast a vote in an already-existing challenge
-		Voted(AccountId, ChallengeId, bool, Balance),
+		/// A user committed a (hidden) vote in an already-existing challenge
+		Committed(AccountId, ChallengeId, Balance),
+
+		/// The commit stage of a challenge has ended, and votes may now be revealed
+		RevealStageStarted(RegistryId, ListingId, ChallengeId),
+
+		/// A user revealed a previously committed vote
+		Revealed(AccountId, ChallengeId, bool, Balance),
+
+		/// A user replaced the commitment of their vote
+		VoteChanged(AccountId, ChallengeId),
+
+		/// A user withdrew their vote, and its stake was released
+		VoteWithdrawn(AccountId, ChallengeId, Balance),
 
 		/// A challenge has been resolved and the challenged listing included or excluded from the registry.
 		/// This does not guarantee that the status of the challenged listing in the registry has changed.
 		/// For example, a previously-listed item may have passed the challenge, or a new applicant may have
 		/// failed the challenge.
-		Resolved(ListingId, bool),
+		Resolved(RegistryId, ListingId, bool),
+
+		/// A challenge did not reach the vote quorum. The status quo wins: the listing is kept, or goes back to its apply stage if it was an application, and no deposit is forfeited.
+		QuorumNotReached(RegistryId, ListingId, ChallengeId),
 
 		/// A new, previously un-registered listing has been added to the Registry
-		Accepted(ListingId),
+		Accepted(RegistryId, ListingId),
 
 		/// A previously-registered listing, or a proposla has been rejected.
-		Rejected(ListingId),
+		Rejected(RegistryId, ListingId),
+
+		/// A voter has claimed their stake and reward from a resolved challenge.
+		RewardClaimed(AccountId, ChallengeId, Balance),
+
+		/// A vote forfeited in a resolved challenge was dropped. Contains the forfeited stake.
+		VoteSwept(AccountId, ChallengeId, Balance),
+
+		/// The owner of a listing asked to exit the registry. The listing leaves the registry at the
+		/// given block, unless it is challenged and loses first.
+		ExitInitiated(RegistryId, ListingId, BlockNumber),
+
+		/// A listing has left the registry at its owner's request, and the deposit was returned.
+		Exited(RegistryId, ListingId),
+
+		/// The owner of a listing added to its deposit. Contains the amount added.
+		DepositIncreased(RegistryId, ListingId, Balance),
+
+		/// The owner of a listing withdrew part of its deposit. Contains the amount withdrawn.
+		DepositWithdrawn(RegistryId, ListingId, Balance),
+
+		/// A user has applied an overdue status update to a listing. Contains the tip they were paid.
+		StatusUpdated(AccountId, RegistryId, ListingId, Balance),
+
+		/// A payment out of the pot failed while resolving a listing, so the amount was added to what the
+		/// pot owes the account. This only happens if the pot holds less than the stakes and rewards it owes.
+		PayoutFailed(AccountId, Balance),
+
+		/// An account was paid what the pot owed it after failed payments.
+		OwedClaimed(AccountId, Balance),
 	}
 );
 
-decl_module! {
-	pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+decl_error! {
+	/// Errors of the TCR module
+	pub enum Error for Module<T: Trait<I>, I: Instance> {
+		/// The registry does not exist
+		RegistryNotFound,
+		/// The deposit is below the minimum deposit
+		DepositTooLow,
+		/// The listing data is longer than `MaxListingDataLen`
+		ListingDataTooLong,
+		/// In a content-addressed registry, the given listing id is not the one derived from the listing data
+		ListingIdNotDerivedFromData,
+		/// The registry is not content-addressed, so the proposer must pick the listing id
+		ListingIdRequired,
+		/// A listing with this id already exists
+		ListingAlreadyExists,
+		/// The listing does not exist
+		ListingNotFound,
+		/// The sender does not own the listing
+		NotListingOwner,
+		/// The listing is not in the registry
+		NotInRegistry,
+		/// The listing is already challenged
+		AlreadyChallenged,
+		/// The listing is challenged, which prevents this operation
+		ListingChallenged,
+		/// The listing is not challenged
+		NotChallenged,
+		/// Owners cannot challenge their own listing
+		CannotChallengeOwnListing,
+		/// The challenge deposit is below the deposit of the listing
+		ChallengeDepositTooLow,
+		/// The commit stage of the challenge has ended
+		CommitStageEnded,
+		/// The reveal stage of the challenge has not started yet
+		RevealStageNotStarted,
+		/// The reveal stage of the challenge has ended
+		RevealStageEnded,
+		/// The sender has already voted in this challenge
+		AlreadyVoted,
+		/// The sender has not voted in this challenge
+		VoteNotFound,
+		/// The vote has already been revealed
+		AlreadyRevealed,
+		/// The revealed vote and salt do not match the committed hash
+		CommitmentMismatch,
+		/// The amount to withdraw is more than the deposit
+		WithdrawalTooLarge,
+		/// The owner has already asked to exit the registry
+		ExitAlreadyInitiated,
+		/// The challenge does not exist
+		ChallengeNotFound,
+		/// The challenge has not been resolved yet
+		ChallengeNotResolved,
+		/// The sender has already claimed their stake and reward from this challenge
+		AlreadyClaimed,
+		/// The vote won or was not decided, so its stake is claimed with `claim_reward`
+		VoteNotForfeited,
+		/// The vote stake is below `MinVoteStake`
+		VoteStakeTooLow,
+		/// The sender cannot afford the deposit
+		InsufficientBalance,
+		/// A block number or balance overflowed
+		Overflow,
+		/// The listing is neither an expired application nor under an expired challenge
+		NothingToUpdate,
+		/// The pot owes nothing to the sender
+		NothingOwed,
+		/// The minimum deposit of a registry is below `MinDepositFloor`
+		MinDepositTooLow,
+		/// A stage of a registry is shorter than `MinStageLen`
+		StageTooShort,
+	}
+}
 
-		// const MinDeposit: BalanceOf<T> = T::MinDeposit::get();
-		// const ApplyStangeLen: T::BlockNumber = T::ApplyStageLen::get();
-		// const CommitStageLen: T::BlockNumber = T::CommitStageLen::get();
+decl_module! {
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		type Error = Error<T, I>;
+
+		// const DispensationPct: Percent = T::DispensationPct::get();
+		// const VoteQuorum: BalanceOf<T, I> = T::VoteQuorum::get();
+		// const MinVoteStake: BalanceOf<T, I> = T::MinVoteStake::get();
+		// const PassThreshold: Perbill = T::PassThreshold::get();
+		// const ExitDelay: T::BlockNumber = T::ExitDelay::get();
+		// const MaxListingDataLen: u32 = T::MaxListingDataLen::get();
+		// const MinDepositFloor: BalanceOf<T, I> = T::MinDepositFloor::get();
+		// const MinStageLen: T::BlockNumber = T::MinStageLen::get();
 
 		// Initialize events for this module.
 		fn deposit_event() = default;
 
-		/// Propose a listing on the registry.
-		fn propose(origin, proposed_listing: ListingIdOf<T>, deposit: BalanceOf<T>) -> DispatchResult {
+		/// Create a registry with the given parameters, staking `T::RegistryDeposit`.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::create_registry())]
+		fn create_registry(origin, params: RegistryParamsOf<T, I>) -> DispatchResult {
+			let creator = ensure_signed(origin)?;
+			Self::check_min_deposit(params.min_deposit)?;
+			Self::check_stage_lengths(params.apply_stage_len, params.commit_stage_len, params.reveal_stage_len)?;
+
+			// Stake the registry deposit.
+			let deposit = T::RegistryDeposit::get();
+			Self::stake(&creator, deposit)?;
+
+			// Store the registry and its parameters.
+			let registry_id = Self::insert_registry(creator.clone(), deposit, params);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::RegistryCreated(creator, registry_id, deposit));
+			Ok(())
+		}
+
+		/// Change the minimum deposit needed to propose a listing in a registry.
+		#[weight = SimpleDispatchInfo::FixedOperational(weights::set_min_deposit())]
+		fn set_min_deposit(origin, registry_id: RegistryId, min_deposit: BalanceOf<T, I>) -> DispatchResult {
+			T::ParamOrigin::ensure_origin(origin)?;
+			ensure!(<Registries<T, I>>::exists(registry_id), Error::<T, I>::RegistryNotFound);
+			Self::check_min_deposit(min_deposit)?;
+
+			MinDeposit::<T, I>::insert(registry_id, min_deposit);
+
+			Self::deposit_event(RawEvent::MinDepositChanged(registry_id, min_deposit));
+			Ok(())
+		}
+
+		/// Change the lengths of the apply, commit and reveal stages of a registry.
+		#[weight = SimpleDispatchInfo::FixedOperational(weights::set_stage_lengths())]
+		fn set_stage_lengths(
+			origin,
+			registry_id: RegistryId,
+			apply_stage_len: T::BlockNumber,
+			commit_stage_len: T::BlockNumber,
+			reveal_stage_len: T::BlockNumber
+		) -> DispatchResult {
+			T::ParamOrigin::ensure_origin(origin)?;
+			ensure!(<Registries<T, I>>::exists(registry_id), Error::<T, I>::RegistryNotFound);
+			Self::check_stage_lengths(apply_stage_len, commit_stage_len, reveal_stage_len)?;
+
+			ApplyStageLen::<T, I>::insert(registry_id, apply_stage_len);
+			CommitStageLen::<T, I>::insert(registry_id, commit_stage_len);
+			RevealStageLen::<T, I>::insert(registry_id, reveal_stage_len);
+
+			Self::deposit_event(RawEvent::StageLengthsChanged(registry_id, apply_stage_len, commit_stage_len, reveal_stage_len));
+			Ok(())
+		}
+
+		/// Propose a listing on a registry, describing what is being listed with `data`.
+		/// In a content-addressed registry, the listing id is derived from `data`, and
+		/// `proposed_listing` can be left out. Otherwise, proposers pick the id.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::propose(T::MaxListingDataLen::get()))]
+		fn propose(
+			origin,
+			registry_id: RegistryId,
+			proposed_listing: Option<ListingIdOf<T, I>>,
+			deposit: BalanceOf<T, I>,
+			data: Vec<u8>
+		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 
-			ensure!(deposit >= T::MinDeposit::get(), "deposit should be more than min_deposit");
+			ensure!(<Registries<T, I>>::exists(registry_id), Error::<T, I>::RegistryNotFound);
+			ensure!(data.len() <= T::MaxListingDataLen::get() as usize, Error::<T, I>::ListingDataTooLong);
+
+			let proposed_listing = match T::ListingIds::from_data_hash(T::Hashing::hash(&data)) {
+				Some(content_id) => {
+					ensure!(proposed_listing.map_or(true, |id| id == content_id), Error::<T, I>::ListingIdNotDerivedFromData);
+					content_id
+				}
+				None => proposed_listing.ok_or(Error::<T, I>::ListingIdRequired)?,
+			};
+
+			ensure!(deposit >= Self::min_deposit(registry_id), Error::<T, I>::DepositTooLow);
 
-			ensure!(!<Listings<T>>::exists(&proposed_listing), "Listing already exists");
+			ensure!(!<Listings<T, I>>::exists(registry_id, &proposed_listing), Error::<T, I>::ListingAlreadyExists);
 
 			// Set application expiry for the listing.
 			// Generating a future timestamp by adding the apply stage length.
 			let now = <system::Module<T>>::block_number();
-			let app_exp = now.checked_add(&T::ApplyStageLen::get()).ok_or("Overflow when setting application expiry.")?;
+			let app_exp = now.checked_add(&Self::apply_stage_len(registry_id)).ok_or(Error::<T, I>::Overflow)?;
 
 			// Create a new listing instance and store it.
-			let listing = ListingDetailOf::<T> {
+			let listing = ListingDetailOf::<T, I> {
 				deposit,
 				owner: sender.clone(),
 				application_expiry: Some(app_exp),
 				in_registry: false,
 				challenge_id: None,
+				exit_expiry: None,
 			};
 
-			// Reserve the application deposit.
-			T::Currency::reserve(&sender, deposit)
-				.map_err(|_| "Proposer can't afford deposit")?;
+			// Stake the application deposit.
+			Self::stake(&sender, deposit)?;
 
 			// Add the listing to the maps
-			<Listings<T>>::insert(&proposed_listing, listing);
-			<ListingsToUpdate<T>>::append_or_insert(app_exp, &vec![proposed_listing]);
+			<Listings<T, I>>::insert(registry_id, &proposed_listing, listing);
+			<ListingData<T, I>>::insert(registry_id, &proposed_listing, &data);
+			Self::index_application(registry_id, proposed_listing);
+			Self::index_owned_listing(&sender, registry_id, proposed_listing);
+			<ListingsToUpdate<T, I>>::append_or_insert(app_exp, &vec![(registry_id, proposed_listing)]);
 
 			// Raise the event.
-			Self::deposit_event(RawEvent::Proposed(sender, proposed_listing, deposit));
+			Self::deposit_event(RawEvent::Proposed(sender, registry_id, proposed_listing, deposit, data));
 			Ok(())
 		}
 
 		/// Challenge a listing
-		fn challenge(origin, listing_id: ListingIdOf<T>, deposit: BalanceOf<T>) -> DispatchResult {
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::challenge())]
+		fn challenge(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, deposit: BalanceOf<T, I>) -> DispatchResult {
 			let challenger = ensure_signed(origin)?;
 
 			// Ensure the listing exists and grab it
-			ensure!(<Listings<T>>::exists(&listing_id), "Listing not found.");
-			let mut listing = Self::listings(&listing_id);
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let mut listing = Self::listings(registry_id, &listing_id);
 
-			ensure!(listing.challenge_id == None, "Listing is already challenged.");
-			ensure!(listing.owner != challenger, "You cannot challenge your own listing.");
-			ensure!(deposit >= listing.deposit, "Not enough deposit to challenge.");
+			ensure!(listing.challenge_id == None, Error::<T, I>::AlreadyChallenged);
+			ensure!(listing.owner != challenger, Error::<T, I>::CannotChallengeOwnListing);
+			ensure!(deposit >= listing.deposit, Error::<T, I>::ChallengeDepositTooLow);
 
-			// Calculate end of voting
+			// Calculate end of the commit and reveal stages
 			let now = <system::Module<T>>::block_number();
-			let voting_exp = now.checked_add(&T::CommitStageLen::get()).ok_or("Overflow when setting voting expiry.")?;
+			let commit_end = now.checked_add(&Self::commit_stage_len(registry_id)).ok_or(Error::<T, I>::Overflow)?;
+			let reveal_end = commit_end.checked_add(&Self::reveal_stage_len(registry_id)).ok_or(Error::<T, I>::Overflow)?;
 
-			// If the listing was an unchallenged application, that is now irrelevant
-			listing.application_expiry = None;
+			// An application whose apply stage is over may not have been promoted yet, if its update
+			// is held back by the settlement budget. It is promoted before being challenged.
+			let expired_application = listing.application_expiry.map_or(false, |expiry| expiry < now);
+
+			// If the listing was an unchallenged application, that is now irrelevant, unless the
+			// challenge is voided. The expiry is kept on the challenge for that case.
+			let application_expiry = listing.application_expiry.take();
 
 			// Update the listing's corresponding challenge Id
-			let challenge_id = NextChallengeId::get();
+			let challenge_id = NextChallengeId::<I>::get();
 			listing.challenge_id = Some(challenge_id);
 
-			let challenge = ChallengeDetailOf::<T> {
+			let challenge = ChallengeDetailOf::<T, I> {
+				registry_id,
 				listing_id: listing_id.clone(),
 				deposit: deposit.clone(),
 				owner: challenger.clone(),
 				total_aye: listing.deposit,
 				total_nay: deposit,
-				votes: Vec::new(),
+				commit_end,
+				reveal_end,
+				vote_count: 0,
+				committed_stake: Zero::zero(),
+				aye_votes: 0,
+				nay_votes: 0,
+				outcome: None,
+				reward_pool: Zero::zero(),
+				winning_stake: Zero::zero(),
+				quorum_met: false,
+				application_expiry,
 			};
 
-			// Reserve the deposit for challenge.
-			T::Currency::reserve(&challenger, deposit)
-				.map_err(|_| "Challenger can't afford the deposit")?;
+			// Stake the deposit for challenge.
+			Self::stake(&challenger, deposit)?;
+
+			if expired_application {
+				Self::promote_application(registry_id, listing_id, &mut listing);
+			}
 
 			// Update storage items
-			NextChallengeId::put(challenge_id + 1);
-			<Challenges<T>>::insert(challenge_id, challenge);
-			<Listings<T>>::insert(&listing_id, listing);
-			<ListingsToUpdate<T>>::append_or_insert(voting_exp, &vec![listing_id]);
+			NextChallengeId::<I>::put(challenge_id + 1);
+			<Challenges<T, I>>::insert(challenge_id, challenge);
+			Self::index_open_challenge(challenge_id);
+			Self::index_opened_challenge(&challenger, challenge_id);
+			<Listings<T, I>>::insert(registry_id, &listing_id, listing);
+			<ListingsToUpdate<T, I>>::append_or_insert(commit_end, &vec![(registry_id, listing_id)]);
+			<ListingsToUpdate<T, I>>::append_or_insert(reveal_end, &vec![(registry_id, listing_id)]);
 
 			// Raise the event.
-			Self::deposit_event(RawEvent::Challenged(challenger, listing_id, challenge_id, deposit));
+			Self::deposit_event(RawEvent::Challenged(challenger, registry_id, listing_id, challenge_id, deposit));
 			Ok(())
 		}
 
-		/// Registers a vote for a particular challenge.
-		fn vote(origin, listing_id: ListingIdOf<T>, vote_bool: bool, deposit: BalanceOf<T>) -> DispatchResult {
+		/// Commits a hidden vote for a particular challenge. The commitment is the hash of
+		/// `(voter, challenge_id, aye_or_nay, salt)`, with a random 32-byte salt, so that it cannot be
+		/// matched against or copied by other voters. The vote only counts once it is revealed with
+		/// `reveal_vote`.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::commit_vote())]
+		fn commit_vote(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, commitment: T::Hash, deposit: BalanceOf<T, I>) -> DispatchResult {
 			let voter = ensure_signed(origin)?;
 
 			// Check listing exists and is challenged.
-			ensure!(<Listings<T>>::exists(&listing_id), "Listing does not exist.");
-			let challenge_id = <Listings<T>>::get(&listing_id).challenge_id;
-			ensure!(challenge_id != None, "Listing is not challenged.");
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let challenge_id = <Listings<T, I>>::get(registry_id, &listing_id).challenge_id;
+			ensure!(challenge_id != None, Error::<T, I>::NotChallenged);
 			let challenge_id = challenge_id.expect("Just checked to ensure it's not None; qed");
 
+			// Check the challenge is still in its commit stage, and this is the voter's only commitment.
+			// Repeat votes are rejected rather than merged, as they could not be revealed separately.
+			let mut challenge = <Challenges<T, I>>::get(challenge_id);
+			let now = <system::Module<T>>::block_number();
+			ensure!(now <= challenge.commit_end, Error::<T, I>::CommitStageEnded);
+			ensure!(!<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::AlreadyVoted);
+			ensure!(deposit >= T::MinVoteStake::get(), Error::<T, I>::VoteStakeTooLow);
+
 			// Deduct the deposit for vote.
-			T::Currency::reserve(&voter, deposit)
-				.map_err(|_| "Voter can't afford the deposit")?;
+			Self::stake(&voter, deposit)?;
 
-			// Update votes in challenge storage
-			let vote = VoteOf::<T> {
-				voter: voter.clone(),
-				aye_or_nay: vote_bool,
+			// Record the commitment. The tallies are only updated on reveal.
+			let vote = VoteOf::<T, I> {
+				commitment,
+				aye_or_nay: None,
 				deposit: deposit,
 			};
-			let mut challenge = <Challenges<T>>::get(challenge_id);
-			challenge.votes.push(vote);
+			challenge.vote_count += 1;
+			challenge.committed_stake += deposit;
+
+			// Update storage.
+			<Votes<T, I>>::insert(challenge_id, &voter, vote);
+			<Challenges<T, I>>::insert(challenge_id, challenge);
+			Self::index_cast_vote(&voter, challenge_id);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::Committed(voter, challenge_id, deposit));
+			Ok(())
+		}
+
+		/// Reveals a vote previously committed with `commit_vote`, counting it towards the challenge.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::reveal_vote())]
+		fn reveal_vote(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, vote_bool: bool, salt: [u8; 32]) -> DispatchResult {
+			let voter = ensure_signed(origin)?;
+
+			// Check listing exists and is challenged.
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let challenge_id = <Listings<T, I>>::get(registry_id, &listing_id).challenge_id;
+			ensure!(challenge_id != None, Error::<T, I>::NotChallenged);
+			let challenge_id = challenge_id.expect("Just checked to ensure it's not None; qed");
+
+			// Check the challenge is in its reveal stage.
+			let mut challenge = <Challenges<T, I>>::get(challenge_id);
+			let now = <system::Module<T>>::block_number();
+			ensure!(now > challenge.commit_end, Error::<T, I>::RevealStageNotStarted);
+			ensure!(now <= challenge.reveal_end, Error::<T, I>::RevealStageEnded);
+
+			// Find the voter's commitment and check it matches the revealed vote.
+			ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::VoteNotFound);
+			let mut vote = <Votes<T, I>>::get(challenge_id, &voter);
+			ensure!(vote.aye_or_nay == None, Error::<T, I>::AlreadyRevealed);
+			let revealed = T::Hashing::hash_of(&(&voter, challenge_id, vote_bool, salt));
+			ensure!(vote.commitment == revealed, Error::<T, I>::CommitmentMismatch);
+			vote.aye_or_nay = Some(vote_bool);
+			let deposit = vote.deposit;
+
+			// Only revealed votes count towards the tallies.
 			if vote_bool {
 				challenge.total_aye += deposit;
+				challenge.aye_votes += 1;
 			}
 			else {
 				challenge.total_nay += deposit;
+				challenge.nay_votes += 1;
 			}
 
 			// Update storage.
-			<Challenges<T>>::insert(challenge_id, challenge);
+			<Votes<T, I>>::insert(challenge_id, &voter, vote);
+			<Challenges<T, I>>::insert(challenge_id, challenge);
 
 			// Raise the event.
-			Self::deposit_event(RawEvent::Voted(voter, challenge_id, vote_bool, deposit));
+			Self::deposit_event(RawEvent::Revealed(voter, challenge_id, vote_bool, deposit));
 			Ok(())
 		}
 
-		/// Resolves challenges that expire during this block
-		fn on_finalize(now: T::BlockNumber) {
+		/// Replaces the commitment of the sender's vote with a new one, built like in `commit_vote`.
+		/// Commit stage only: votes are not counted until they are revealed, so changing one never
+		/// touches `total_aye` or `total_nay`.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::change_vote())]
+		fn change_vote(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, commitment: T::Hash) -> DispatchResult {
+			let voter = ensure_signed(origin)?;
+
+			// Check listing exists and is challenged.
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let challenge_id = <Listings<T, I>>::get(registry_id, &listing_id).challenge_id;
+			ensure!(challenge_id != None, Error::<T, I>::NotChallenged);
+			let challenge_id = challenge_id.expect("Just checked to ensure it's not None; qed");
+
+			// Check the challenge is still in its commit stage, and the voter has voted.
+			let now = <system::Module<T>>::block_number();
+			ensure!(now <= <Challenges<T, I>>::get(challenge_id).commit_end, Error::<T, I>::CommitStageEnded);
+			ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::VoteNotFound);
+
+			// Update storage.
+			<Votes<T, I>>::mutate(challenge_id, &voter, |vote| vote.commitment = commitment);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::VoteChanged(voter, challenge_id));
+			Ok(())
+		}
+
+		/// Withdraws the sender's vote and releases its stake. Votes can only be withdrawn during the
+		/// commit stage, before they count towards the tallies.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::withdraw_vote())]
+		fn withdraw_vote(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>) -> DispatchResult {
+			let voter = ensure_signed(origin)?;
+
+			// Check listing exists and is challenged.
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let challenge_id = <Listings<T, I>>::get(registry_id, &listing_id).challenge_id;
+			ensure!(challenge_id != None, Error::<T, I>::NotChallenged);
+			let challenge_id = challenge_id.expect("Just checked to ensure it's not None; qed");
+
+			// Check the challenge is still in its commit stage, and the voter has voted.
+			let mut challenge = <Challenges<T, I>>::get(challenge_id);
+			let now = <system::Module<T>>::block_number();
+			ensure!(now <= challenge.commit_end, Error::<T, I>::CommitStageEnded);
+			ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::VoteNotFound);
+
+			// Release the stake. Unrevealed votes are not part of the tallies, so those stay the same.
+			let vote = <Votes<T, I>>::get(challenge_id, &voter);
+			Self::release_stake(&voter, vote.deposit)?;
+			challenge.vote_count -= 1;
+			challenge.committed_stake -= vote.deposit;
+
+			// Update storage.
+			<Votes<T, I>>::remove(challenge_id, &voter);
+			<Challenges<T, I>>::insert(challenge_id, challenge);
+			Self::unindex_cast_vote(&voter, challenge_id);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::VoteWithdrawn(voter, challenge_id, vote.deposit));
+			Ok(())
+		}
+
+		/// Add to the deposit of the sender's listing, raising the deposit needed to challenge it.
+		/// The deposit cannot change during a challenge, as it is part of the challenge's tally.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::deposit_more())]
+		fn deposit_more(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, amount: BalanceOf<T, I>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Ensure the listing exists and grab it
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let mut listing = Self::listings(registry_id, &listing_id);
+
+			ensure!(listing.owner == sender, Error::<T, I>::NotListingOwner);
+			ensure!(listing.challenge_id == None, Error::<T, I>::ListingChallenged);
+			listing.deposit = listing.deposit.checked_add(&amount).ok_or(Error::<T, I>::Overflow)?;
+
+			// Stake the additional deposit.
+			Self::stake(&sender, amount)?;
+
+			// Update storage.
+			<Listings<T, I>>::insert(registry_id, &listing_id, listing);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::DepositIncreased(registry_id, listing_id, amount));
+			Ok(())
+		}
+
+		/// Withdraw part of the deposit of the sender's listing, as long as `MinDeposit` remains.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::withdraw_excess())]
+		fn withdraw_excess(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>, amount: BalanceOf<T, I>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Ensure the listing exists and grab it
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let mut listing = Self::listings(registry_id, &listing_id);
+
+			ensure!(listing.owner == sender, Error::<T, I>::NotListingOwner);
+			ensure!(listing.challenge_id == None, Error::<T, I>::ListingChallenged);
+			ensure!(listing.deposit >= amount, Error::<T, I>::WithdrawalTooLarge);
+			listing.deposit -= amount;
+			ensure!(listing.deposit >= Self::min_deposit(registry_id), Error::<T, I>::DepositTooLow);
+
+			// Release the withdrawn deposit.
+			Self::release_stake(&sender, amount)?;
 
-			// If nothing needs updated, return early
-			if !<ListingsToUpdate<T>>::exists(now) {
-				return ();
+			// Update storage.
+			<Listings<T, I>>::insert(registry_id, &listing_id, listing);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::DepositWithdrawn(registry_id, listing_id, amount));
+			Ok(())
+		}
+
+		/// Remove the sender's listing from the registry and release its deposit.
+		/// With a non-zero `ExitDelay`, the listing only leaves the registry once the delay has passed.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::exit())]
+		fn exit(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Ensure the listing exists and grab it
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let mut listing = Self::listings(registry_id, &listing_id);
+
+			ensure!(listing.owner == sender, Error::<T, I>::NotListingOwner);
+			ensure!(listing.in_registry, Error::<T, I>::NotInRegistry);
+			ensure!(listing.challenge_id == None, Error::<T, I>::ListingChallenged);
+			ensure!(listing.exit_expiry == None, Error::<T, I>::ExitAlreadyInitiated);
+
+			let delay = T::ExitDelay::get();
+			if delay.is_zero() {
+				Self::complete_exit(registry_id, listing_id, &listing);
 			}
+			else {
+				// Schedule the exit, leaving time for pending challenges.
+				let now = <system::Module<T>>::block_number();
+				let exit_exp = now.checked_add(&delay).ok_or(Error::<T, I>::Overflow)?;
+				listing.exit_expiry = Some(exit_exp);
 
-			// Take the listings in question from the runtime storage
-			let listing_ids = <ListingsToUpdate<T>>::get(now);
-			<ListingsToUpdate<T>>::remove(now);
+				<Listings<T, I>>::insert(registry_id, &listing_id, listing);
+				<ListingsToUpdate<T, I>>::append_or_insert(exit_exp, &vec![(registry_id, listing_id)]);
 
-			for listing_id in listing_ids.iter() {
-				// Grab the listing
-				let mut listing = <Listings<T>>::get(&listing_id);
+				Self::deposit_event(RawEvent::ExitInitiated(registry_id, listing_id, exit_exp));
+			}
 
-				// See whether we're here because of application expiry
-				if listing.application_expiry == Some(now) {
-					// See if the application has gone unchallenged
-					if listing.challenge_id == None {
-						Self::promote_application(*listing_id, &mut listing);
+			Ok(())
+		}
+
+		/// Releases the sender's stake in a resolved challenge, and pays out their share of the reward pool
+		/// if they voted with the winning side. Stakes on the losing side are forfeited to the slash
+		/// handler, unless the challenge did not reach the vote quorum. Unrevealed stakes are always
+		/// forfeited, so that keeping a losing vote hidden is no better than revealing it. Forfeited
+		/// stakes leave the pot when the challenge is resolved, so claiming such a vote only drops it,
+		/// like `sweep_vote`, even once the challenge itself has been dropped.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::claim_reward())]
+		fn claim_reward(origin, challenge_id: ChallengeId) -> DispatchResult {
+			let voter = ensure_signed(origin)?;
+
+			// Check the challenge is resolved, and the voter has not claimed yet. A vote outlives its
+			// challenge only if it was forfeited.
+			let challenge = if <Challenges<T, I>>::exists(challenge_id) {
+				let challenge = <Challenges<T, I>>::get(challenge_id);
+				ensure!(challenge.outcome.is_some(), Error::<T, I>::ChallengeNotResolved);
+				ensure!(!<Claimed<T, I>>::get(challenge_id, &voter), Error::<T, I>::AlreadyClaimed);
+				Some(challenge)
+			}
+			else {
+				ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::ChallengeNotFound);
+				None
+			};
+
+			ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::VoteNotFound);
+			let vote = <Votes<T, I>>::get(challenge_id, &voter);
+
+			let reward = match challenge {
+				Some(mut challenge) if !Self::is_forfeited(&challenge, &vote) => {
+					let reward = if challenge.quorum_met {
+						// Release the stake and pay the pro rata share of what is left of the reward
+						// pool, rounded down. Both shrink with each claim, so the last claimant gets
+						// the remainder and the pool is never overpaid.
+						let reward: BalanceOf<T, I> = multiply_by_rational(
+							challenge.reward_pool.saturated_into(),
+							vote.deposit.saturated_into(),
+							challenge.winning_stake.saturated_into(),
+						).map(|reward| reward.saturated_into()).unwrap_or_else(|_| Zero::zero());
+						Self::pay_out(&voter, reward)?;
+						Self::release_stake(&voter, vote.deposit)?;
+						challenge.reward_pool = challenge.reward_pool.saturating_sub(reward);
+						challenge.winning_stake = challenge.winning_stake.saturating_sub(vote.deposit);
+						reward
 					}
 					else {
-						// Some listings will have been marked for update at this block because their
-						// application would have expired now, but have been challenged in the meantime.
-						listing.application_expiry = None;
-					}
+						// Challenges without quorum were not decided, so revealed stakes are simply released.
+						Self::release_stake(&voter, vote.deposit)?;
+						Zero::zero()
+					};
+
+					// Update storage. The challenge is dropped once the last of these votes is claimed.
+					challenge.vote_count -= 1;
+					<Claimed<T, I>>::insert(challenge_id, &voter, true);
+					Self::drop_vote(challenge_id, &voter);
+					Self::store_resolved_challenge(challenge_id, challenge);
+					reward
+				}
+				_ => {
+					// The stake went to the slash handler when the challenge was resolved, and the vote
+					// is not counted in `vote_count`.
+					Self::forfeit_stake(&voter, vote.deposit);
+					Self::drop_vote(challenge_id, &voter);
+					Zero::zero()
+				}
+			};
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::RewardClaimed(voter, challenge_id, reward));
+			Ok(())
+		}
+
+		/// Drops a vote forfeited in a resolved challenge: one left unrevealed, or on the losing side
+		/// of a challenge that reached the quorum. Its stake left the pot when the challenge was
+		/// resolved, so this only clears the voter's entries. Anyone can call this, even once the
+		/// challenge itself has been dropped.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::sweep_vote())]
+		fn sweep_vote(origin, challenge_id: ChallengeId, voter: T::AccountId) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			// Check the vote was forfeited. A vote outlives its challenge only if it was, as the
+			// challenge is dropped once the other votes are claimed.
+			ensure!(<Votes<T, I>>::exists(challenge_id, &voter), Error::<T, I>::VoteNotFound);
+			let vote = <Votes<T, I>>::get(challenge_id, &voter);
+			if <Challenges<T, I>>::exists(challenge_id) {
+				let challenge = <Challenges<T, I>>::get(challenge_id);
+				ensure!(challenge.outcome.is_some(), Error::<T, I>::ChallengeNotResolved);
+				ensure!(Self::is_forfeited(&challenge, &vote), Error::<T, I>::VoteNotForfeited);
+			}
+
+			// Update storage.
+			Self::forfeit_stake(&voter, vote.deposit);
+			Self::drop_vote(challenge_id, &voter);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::VoteSwept(voter, challenge_id, vote.deposit));
+			Ok(())
+		}
+
+		/// Pays the sender what the pot owes them after payments out of it failed while resolving
+		/// listings. This fails for as long as the pot still holds too little.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::claim_owed())]
+		fn claim_owed(origin) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(<Owed<T, I>>::exists(&sender), Error::<T, I>::NothingOwed);
+			let amount = <Owed<T, I>>::get(&sender);
+
+			// Pay first, as the transfer is what failed before.
+			Self::pay_out(&sender, amount)?;
+			<Owed<T, I>>::remove(&sender);
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::OwedClaimed(sender, amount));
+			Ok(())
+		}
+
+		/// Promotes an unchallenged application whose application stage is over, or settles a challenge
+		/// whose reveal stage is over, in case `on_finalize` has not done it yet. Anyone can call this,
+		/// and the caller is paid `T::UpdateTip` out of the pot's surplus, if there is enough of it.
+		#[weight = SimpleDispatchInfo::FixedNormal(weights::update_status())]
+		fn update_status(origin, registry_id: RegistryId, listing_id: ListingIdOf<T, I>) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+
+			// Ensure the listing exists and grab it
+			ensure!(<Listings<T, I>>::exists(registry_id, &listing_id), Error::<T, I>::ListingNotFound);
+			let mut listing = Self::listings(registry_id, &listing_id);
+
+			// Check there is an overdue update
+			let now = <system::Module<T>>::block_number();
+			let expired_application = listing.challenge_id == None
+				&& listing.application_expiry.map_or(false, |expiry| expiry < now);
+			let expired_challenge = listing.challenge_id
+				.map_or(false, |challenge_id| <Challenges<T, I>>::get(challenge_id).reveal_end < now);
+			ensure!(expired_application || expired_challenge, Error::<T, I>::NothingToUpdate);
+
+			// Pay the tip first, as the update cannot be undone if the transfer fails
+			let tip = T::UpdateTip::get().min(Self::pot_surplus());
+			if !tip.is_zero() {
+				Self::pay_out(&sender, tip)?;
+				<PotSurplus<T, I>>::mutate(|surplus| *surplus -= tip);
+			}
+
+			// Apply the update. The entries left in `ListingsToUpdate` are ignored when their turn comes.
+			if expired_application {
+				Self::promote_application(registry_id, listing_id, &mut listing);
+			}
+			else {
+				Self::settle_challenge(registry_id, listing_id, &mut listing);
+			}
+
+			// Raise the event.
+			Self::deposit_event(RawEvent::StatusUpdated(sender, registry_id, listing_id, tip));
+			Ok(())
+		}
+
+		/// Resolves challenges that expire during this block
+		fn on_finalize(now: T::BlockNumber) {
+
+			// Drop the challenge outcomes whose retention period is over
+			for challenge_id in <OutcomesToPrune<T, I>>::take(now) {
+				ChallengeOutcomes::<T, I>::remove(challenge_id);
+			}
+
+			// Process the status updates due so far, oldest first, within the settlement budget.
+			// Updates that do not fit are left in `ListingsToUpdate`, and `UpdateCursor` points at
+			// the first of them so that later blocks carry on from there.
+			let budget = T::SettlementBudget::get();
+			let mut used: Weight = 0;
+			let mut updated = false;
+			let backlog = Self::update_cursor();
+			let mut cursor = backlog.unwrap_or((now, 0));
+			loop {
+				let (block, position) = cursor;
+				if updated && used.saturating_add(weights::SCHEDULE) > budget {
+					<UpdateCursor<T, I>>::put(cursor);
+					Self::note_finalize_weight(used);
+					return;
 				}
-				else {
-					// Make sure a challenge is epiring
-					match listing.challenge_id {
-						Some(_) => {
-							Self::settle_challenge(*listing_id, &mut listing);
-						}
-						None => { /* Nothing to do. Happens when challenge resolved before application expiry */}
+				let listing_ids = <ListingsToUpdate<T, I>>::get(block);
+				let scheduled = !listing_ids.is_empty();
+				used = used.saturating_add(weights::SCHEDULE);
+
+				for (registry_id, listing_id) in listing_ids.into_iter().skip(position as usize) {
+					// Always make some progress, even if a single update is over budget
+					let weight = weights::update();
+					if updated && used.saturating_add(weight) > budget {
+						<UpdateCursor<T, I>>::put(cursor);
+						Self::note_finalize_weight(used);
+						return;
 					}
+					Self::update_listing(registry_id, listing_id, block);
+					used = used.saturating_add(weight);
+					updated = true;
+					cursor.1 += 1;
+				}
+				if scheduled {
+					<ListingsToUpdate<T, I>>::remove(block);
 				}
+
+				if block >= now {
+					break;
+				}
+				cursor = (block + One::one(), 0);
 			}
 
-			// Return
-			()
+			// Blocks without any update leave storage untouched
+			if backlog.is_some() {
+				<UpdateCursor<T, I>>::kill();
+			}
+			Self::note_finalize_weight(used);
 		}
 	}
 }
 
-impl<T: Trait> Module<T> {
-	pub fn registry_contains(l: ListingIdOf<T>) -> bool {
-		if Listings::<T>::exists(l) {
-			Listings::<T>::get(l).in_registry
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+	/// The account of the TCR's pot.
+	pub fn account_id() -> T::AccountId {
+		T::ModuleId::get().into_account()
+	}
+
+	/// Records the weight used by `on_finalize` in this block, unless it is unchanged.
+	fn note_finalize_weight(used: Weight) {
+		if FinalizeWeight::<I>::get() != used {
+			FinalizeWeight::<I>::put(used);
+		}
+	}
+
+	/// Allocates an id for a new registry, and stores it with its parameters.
+	fn insert_registry(creator: AccountIdOf<T>, deposit: BalanceOf<T, I>, params: RegistryParamsOf<T, I>) -> RegistryId {
+		let registry_id = NextRegistryId::<I>::get();
+		NextRegistryId::<I>::put(registry_id + 1);
+		<Registries<T, I>>::insert(registry_id, RegistryDetailOf::<T, I> { creator, deposit });
+		<MinDeposit<T, I>>::insert(registry_id, params.min_deposit);
+		<ApplyStageLen<T, I>>::insert(registry_id, params.apply_stage_len);
+		<CommitStageLen<T, I>>::insert(registry_id, params.commit_stage_len);
+		<RevealStageLen<T, I>>::insert(registry_id, params.reveal_stage_len);
+		registry_id
+	}
+
+	/// Checks the minimum deposit of a registry against `T::MinDepositFloor`.
+	fn check_min_deposit(min_deposit: BalanceOf<T, I>) -> DispatchResult {
+		ensure!(min_deposit >= T::MinDepositFloor::get(), Error::<T, I>::MinDepositTooLow);
+		Ok(())
+	}
+
+	/// Checks the stage lengths of a registry against `T::MinStageLen`.
+	fn check_stage_lengths(apply: BlockNumberOf<T>, commit: BlockNumberOf<T>, reveal: BlockNumberOf<T>) -> DispatchResult {
+		let min = T::MinStageLen::get();
+		ensure!(apply >= min && commit >= min && reveal >= min, Error::<T, I>::StageTooShort);
+		Ok(())
+	}
+
+	pub fn registry_contains(registry_id: RegistryId, l: ListingIdOf<T, I>) -> bool {
+		if Listings::<T, I>::exists(registry_id, l) {
+			Listings::<T, I>::get(registry_id, l).in_registry
 		}
 		else {
 			false
 		}
 	}
 
-	fn promote_application(listing_id: ListingIdOf<T>, listing: &mut ListingDetailOf<T>) {
+	/// The listing or application with the given id in a registry, if any.
+	pub fn listing(registry_id: RegistryId, listing_id: ListingIdOf<T, I>) -> Option<ListingDetailOf<T, I>> {
+		if Listings::<T, I>::exists(registry_id, listing_id) {
+			Some(Listings::<T, I>::get(registry_id, listing_id))
+		}
+		else {
+			None
+		}
+	}
+
+	/// The challenge with the given id, if any.
+	pub fn challenge(challenge_id: ChallengeId) -> Option<ChallengeDetailOf<T, I>> {
+		if Challenges::<T, I>::exists(challenge_id) {
+			Some(Challenges::<T, I>::get(challenge_id))
+		}
+		else {
+			None
+		}
+	}
+
+	/// A page of the listings currently in a registry, with `limit` listings per page.
+	/// `limit` is capped at `MAX_PAGE_LIMIT`.
+	pub fn registry(registry_id: RegistryId, page: u32, limit: u32) -> Vec<(ListingIdOf<T, I>, ListingDetailOf<T, I>)> {
+		let limit = limit.min(MAX_PAGE_LIMIT);
+		let start = page.saturating_mul(limit);
+		let end = start.saturating_add(limit).min(Self::registry_count(registry_id));
+		(start..end)
+			.map(|position| {
+				let listing_id = Self::registry_listing(registry_id, position);
+				(listing_id, Listings::<T, I>::get(registry_id, listing_id))
+			})
+			.collect()
+	}
+
+	/// A page of the applications that have not been accepted into a registry yet, with `limit`
+	/// applications per page. `limit` is capped at `MAX_PAGE_LIMIT`.
+	pub fn applications(registry_id: RegistryId, page: u32, limit: u32) -> Vec<(ListingIdOf<T, I>, ListingDetailOf<T, I>)> {
+		let limit = limit.min(MAX_PAGE_LIMIT);
+		let start = page.saturating_mul(limit);
+		let end = start.saturating_add(limit).min(Self::applications_count(registry_id));
+		(start..end)
+			.map(|position| {
+				let listing_id = Self::application(registry_id, position);
+				(listing_id, Listings::<T, I>::get(registry_id, listing_id))
+			})
+			.collect()
+	}
+
+	/// The listings and applications owned by an account, with their registries.
+	pub fn listings_of(owner: &AccountIdOf<T>) -> Vec<(RegistryId, ListingIdOf<T, I>)> {
+		(0..Self::owned_listings_count(owner))
+			.map(|position| Self::owned_listing(owner, position))
+			.collect()
+	}
+
+	/// The unresolved challenges opened by an account.
+	pub fn challenges_of(challenger: &AccountIdOf<T>) -> Vec<ChallengeId> {
+		(0..Self::opened_challenges_count(challenger))
+			.map(|position| Self::opened_challenge(challenger, position))
+			.collect()
+	}
+
+	/// The votes an account has not claimed its stake back from yet, with their challenge ids.
+	pub fn votes_of(voter: &AccountIdOf<T>) -> Vec<(ChallengeId, VoteOf<T, I>)> {
+		(0..Self::cast_votes_count(voter))
+			.map(|position| Self::cast_vote(voter, position))
+			.map(|challenge_id| (challenge_id, Votes::<T, I>::get(challenge_id, voter)))
+			.collect()
+	}
+
+	/// Unchallenged applications to a registry waiting for their application stage to end.
+	pub fn pending_applications(registry_id: RegistryId) -> Vec<ListingIdOf<T, I>> {
+		(0..Self::applications_count(registry_id))
+			.map(|position| Self::application(registry_id, position))
+			.filter(|listing_id| Listings::<T, I>::get(registry_id, listing_id).challenge_id == None)
+			.collect()
+	}
+
+	/// Challenges that have not been resolved yet, in any registry.
+	pub fn open_challenges() -> Vec<ChallengeId> {
+		(0..OpenChallengesCount::<I>::get())
+			.map(|position| OpenChallenges::<I>::get(position))
+			.collect()
+	}
+
+	/// Status updates scheduled between the `from` and `to` blocks, inclusive, in any registry.
+	/// Entries of `ListingsToUpdate` that no longer correspond to any deadline are left out.
+	/// At most `MAX_DEADLINE_RANGE` blocks are scanned: a wider range is cut short at
+	/// `from + MAX_DEADLINE_RANGE`.
+	pub fn upcoming_deadlines(
+		from: BlockNumberOf<T>,
+		to: BlockNumberOf<T>,
+	) -> Vec<(BlockNumberOf<T>, RegistryId, ListingIdOf<T, I>)> {
+		let to = to.min(from.saturating_add(MAX_DEADLINE_RANGE.into()));
+		let mut deadlines = Vec::new();
+		let mut block = from;
+		while block <= to {
+			for (registry_id, listing_id) in <ListingsToUpdate<T, I>>::get(block) {
+				let deadline = (block, registry_id, listing_id);
+				if Self::has_deadline_at(registry_id, listing_id, block) && !deadlines.contains(&deadline) {
+					deadlines.push(deadline);
+				}
+			}
+			if block == to {
+				break;
+			}
+			block += One::one();
+		}
+		deadlines
+	}
+
+	/// Whether the listing changes status at the given block.
+	fn has_deadline_at(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, block: BlockNumberOf<T>) -> bool {
+		if !Listings::<T, I>::exists(registry_id, listing_id) {
+			return false;
+		}
+		let listing = Listings::<T, I>::get(registry_id, listing_id);
+		if listing.application_expiry == Some(block) || listing.exit_expiry == Some(block) {
+			return true;
+		}
+		match listing.challenge_id {
+			Some(challenge_id) => {
+				let challenge = Challenges::<T, I>::get(challenge_id);
+				challenge.commit_end == block || challenge.reveal_end == block
+			}
+			None => false,
+		}
+	}
+
+	/// Adds a new application to the registry's `Applications` index.
+	fn index_application(registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		let count = ApplicationsCount::<I>::get(registry_id);
+		<Applications<T, I>>::insert(registry_id, count, listing_id);
+		<ApplicationsIndex<T, I>>::insert(registry_id, listing_id, count);
+		ApplicationsCount::<I>::insert(registry_id, count + 1);
+	}
+
+	/// Removes an application from the registry's `Applications` index, moving the last one into its place.
+	fn unindex_application(registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		if !<ApplicationsIndex<T, I>>::exists(registry_id, listing_id) {
+			return;
+		}
+		let position = <ApplicationsIndex<T, I>>::take(registry_id, listing_id);
+		let last = ApplicationsCount::<I>::get(registry_id) - 1;
+		if position != last {
+			let last_id = <Applications<T, I>>::get(registry_id, last);
+			<Applications<T, I>>::insert(registry_id, position, last_id);
+			<ApplicationsIndex<T, I>>::insert(registry_id, last_id, position);
+		}
+		<Applications<T, I>>::remove(registry_id, last);
+		ApplicationsCount::<I>::insert(registry_id, last);
+	}
+
+	/// Adds a new challenge to the `OpenChallenges` index.
+	fn index_open_challenge(challenge_id: ChallengeId) {
+		let count = OpenChallengesCount::<I>::get();
+		OpenChallenges::<I>::insert(count, challenge_id);
+		OpenChallengesIndex::<I>::insert(challenge_id, count);
+		OpenChallengesCount::<I>::put(count + 1);
+	}
+
+	/// Removes a resolved challenge from the `OpenChallenges` index, moving the last one into its place.
+	fn unindex_open_challenge(challenge_id: ChallengeId) {
+		if !OpenChallengesIndex::<I>::exists(challenge_id) {
+			return;
+		}
+		let position = OpenChallengesIndex::<I>::take(challenge_id);
+		let last = OpenChallengesCount::<I>::get() - 1;
+		if position != last {
+			let last_id = OpenChallenges::<I>::get(last);
+			OpenChallenges::<I>::insert(position, last_id);
+			OpenChallengesIndex::<I>::insert(last_id, position);
+		}
+		OpenChallenges::<I>::remove(last);
+		OpenChallengesCount::<I>::put(last);
+	}
+
+	/// Adds a newly-registered listing to the registry's `RegistryListings` index.
+	fn index_registered(registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		let count = RegistryCount::<I>::get(registry_id);
+		<RegistryListings<T, I>>::insert(registry_id, count, listing_id);
+		<RegistryIndex<T, I>>::insert(registry_id, listing_id, count);
+		RegistryCount::<I>::insert(registry_id, count + 1);
+	}
+
+	/// Removes a listing from the registry's `RegistryListings` index, moving the last one into its place.
+	fn unindex_registered(registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		if !<RegistryIndex<T, I>>::exists(registry_id, listing_id) {
+			return;
+		}
+		let position = <RegistryIndex<T, I>>::take(registry_id, listing_id);
+		let last = RegistryCount::<I>::get(registry_id) - 1;
+		if position != last {
+			let last_id = <RegistryListings<T, I>>::get(registry_id, last);
+			<RegistryListings<T, I>>::insert(registry_id, position, last_id);
+			<RegistryIndex<T, I>>::insert(registry_id, last_id, position);
+		}
+		<RegistryListings<T, I>>::remove(registry_id, last);
+		RegistryCount::<I>::insert(registry_id, last);
+	}
+
+	/// Applies the status update of a listing that was scheduled for the `due` block, which may
+	/// be earlier than the current block if the update was held back by the settlement budget.
+	fn update_listing(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, due: BlockNumberOf<T>) {
+		// Grab the listing
+		let mut listing = <Listings<T, I>>::get(registry_id, &listing_id);
+
+		// See whether we're here because of application expiry
+		if listing.application_expiry == Some(due) {
+			// See if the application has gone unchallenged
+			if listing.challenge_id == None {
+				Self::promote_application(registry_id, listing_id, &mut listing);
+			}
+			else {
+				// Some listings will have been marked for update at this block because their
+				// application would have expired now, but have been challenged in the meantime.
+				listing.application_expiry = None;
+			}
+		}
+		else {
+			// Make sure a challenge is changing stage. Nothing to do if there is no challenge,
+			// which happens when the challenge resolved before application expiry.
+			if let Some(challenge_id) = listing.challenge_id {
+				let challenge = <Challenges<T, I>>::get(challenge_id);
+				if challenge.reveal_end == due {
+					Self::settle_challenge(registry_id, listing_id, &mut listing);
+				}
+				else if challenge.commit_end == due {
+					Self::deposit_event(RawEvent::RevealStageStarted(registry_id, listing_id, challenge_id));
+				}
+			}
+			// A challenged listing exits once the challenge is settled, if it survives.
+			else if listing.exit_expiry == Some(due) {
+				Self::complete_exit(registry_id, listing_id, &listing);
+			}
+		}
+	}
+
+	/// Removes a listing or application from the maps and from every index.
+	fn remove_listing(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, listing: &ListingDetailOf<T, I>) {
+		<Listings<T, I>>::remove(registry_id, &listing_id);
+		<ListingData<T, I>>::remove(registry_id, &listing_id);
+		Self::unindex_registered(registry_id, listing_id);
+		Self::unindex_application(registry_id, listing_id);
+		Self::unindex_owned_listing(&listing.owner, registry_id, listing_id);
+	}
+
+	/// Adds a listing to its owner's `OwnedListings`.
+	fn index_owned_listing(owner: &AccountIdOf<T>, registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		let count = <OwnedListingsCount<T, I>>::get(owner);
+		<OwnedListings<T, I>>::insert(owner, count, (registry_id, listing_id));
+		<OwnedListingsIndex<T, I>>::insert(registry_id, listing_id, count);
+		<OwnedListingsCount<T, I>>::insert(owner, count + 1);
+	}
+
+	/// Removes a listing from its owner's `OwnedListings`, moving their last one into its place.
+	fn unindex_owned_listing(owner: &AccountIdOf<T>, registry_id: RegistryId, listing_id: ListingIdOf<T, I>) {
+		if !<OwnedListingsIndex<T, I>>::exists(registry_id, listing_id) {
+			return;
+		}
+		let position = <OwnedListingsIndex<T, I>>::take(registry_id, listing_id);
+		let last = <OwnedListingsCount<T, I>>::get(owner) - 1;
+		if position != last {
+			let (last_registry_id, last_id) = <OwnedListings<T, I>>::get(owner, last);
+			<OwnedListings<T, I>>::insert(owner, position, (last_registry_id, last_id));
+			<OwnedListingsIndex<T, I>>::insert(last_registry_id, last_id, position);
+		}
+		<OwnedListings<T, I>>::remove(owner, last);
+		if last == 0 {
+			<OwnedListingsCount<T, I>>::remove(owner);
+		}
+		else {
+			<OwnedListingsCount<T, I>>::insert(owner, last);
+		}
+	}
+
+	/// Adds a challenge to its challenger's `OpenedChallenges`.
+	fn index_opened_challenge(challenger: &AccountIdOf<T>, challenge_id: ChallengeId) {
+		let count = <OpenedChallengesCount<T, I>>::get(challenger);
+		<OpenedChallenges<T, I>>::insert(challenger, count, challenge_id);
+		OpenedChallengesIndex::<I>::insert(challenge_id, count);
+		<OpenedChallengesCount<T, I>>::insert(challenger, count + 1);
+	}
+
+	/// Removes a challenge from its challenger's `OpenedChallenges`, moving their last one into its place.
+	fn unindex_opened_challenge(challenger: &AccountIdOf<T>, challenge_id: ChallengeId) {
+		if !OpenedChallengesIndex::<I>::exists(challenge_id) {
+			return;
+		}
+		let position = OpenedChallengesIndex::<I>::take(challenge_id);
+		let last = <OpenedChallengesCount<T, I>>::get(challenger) - 1;
+		if position != last {
+			let last_id = <OpenedChallenges<T, I>>::get(challenger, last);
+			<OpenedChallenges<T, I>>::insert(challenger, position, last_id);
+			OpenedChallengesIndex::<I>::insert(last_id, position);
+		}
+		<OpenedChallenges<T, I>>::remove(challenger, last);
+		if last == 0 {
+			<OpenedChallengesCount<T, I>>::remove(challenger);
+		}
+		else {
+			<OpenedChallengesCount<T, I>>::insert(challenger, last);
+		}
+	}
+
+	/// Adds a vote to its voter's `CastVotes`.
+	fn index_cast_vote(voter: &AccountIdOf<T>, challenge_id: ChallengeId) {
+		let count = <CastVotesCount<T, I>>::get(voter);
+		<CastVotes<T, I>>::insert(voter, count, challenge_id);
+		<CastVotesIndex<T, I>>::insert(challenge_id, voter, count);
+		<CastVotesCount<T, I>>::insert(voter, count + 1);
+	}
+
+	/// Removes a vote from its voter's `CastVotes`, moving their last one into its place.
+	fn unindex_cast_vote(voter: &AccountIdOf<T>, challenge_id: ChallengeId) {
+		if !<CastVotesIndex<T, I>>::exists(challenge_id, voter) {
+			return;
+		}
+		let position = <CastVotesIndex<T, I>>::take(challenge_id, voter);
+		let last = <CastVotesCount<T, I>>::get(voter) - 1;
+		if position != last {
+			let last_id = <CastVotes<T, I>>::get(voter, last);
+			<CastVotes<T, I>>::insert(voter, position, last_id);
+			<CastVotesIndex<T, I>>::insert(last_id, voter, position);
+		}
+		<CastVotes<T, I>>::remove(voter, last);
+		if last == 0 {
+			<CastVotesCount<T, I>>::remove(voter);
+		}
+		else {
+			<CastVotesCount<T, I>>::insert(voter, last);
+		}
+	}
+
+	/// Moves a deposit or stake into the pot, adding it to the account's `TotalStaked`.
+	fn stake(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) -> DispatchResult {
+		T::Currency::transfer(who, &Self::account_id(), amount, ExistenceRequirement::KeepAlive)
+			.map_err(|_| Error::<T, I>::InsufficientBalance)?;
+		<TotalStaked<T, I>>::mutate(who, |total| *total = total.saturating_add(amount));
+		Ok(())
+	}
+
+	/// Returns a deposit or stake from the pot, taking it off the account's `TotalStaked`.
+	fn release_stake(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) -> DispatchResult {
+		Self::pay_out(who, amount)?;
+		Self::deduct_total_staked(who, amount);
+		Ok(())
+	}
+
+	/// Gives up a deposit or stake to the winners of a challenge or to the slash handler, taking it
+	/// off the account's `TotalStaked`.
+	fn forfeit_stake(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) {
+		Self::deduct_total_staked(who, amount);
+	}
+
+	/// Pays out of the pot. The pot holds every outstanding stake and reward on top of its existential
+	/// deposit, so this can only fail if that is no longer the case.
+	fn pay_out(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(());
+		}
+		T::Currency::transfer(&Self::account_id(), who, amount, ExistenceRequirement::KeepAlive)
+	}
+
+	/// Returns a deposit or stake from the pot while resolving a listing, taking it off the
+	/// account's `TotalStaked`. See `pay_out_or_owe`.
+	fn release_stake_or_owe(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) {
+		Self::deduct_total_staked(who, amount);
+		Self::pay_out_or_owe(who, amount);
+	}
+
+	/// Pays out of the pot while resolving a listing, where there is no caller to return an error
+	/// to. A failure means the pot holds less than it owes: the amount is added to `Owed`, for the
+	/// account to claim with `claim_owed` once the pot is topped up, and reported with `PayoutFailed`.
+	fn pay_out_or_owe(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) {
+		if Self::pay_out(who, amount).is_err() {
+			<Owed<T, I>>::mutate(who, |owed| *owed = owed.saturating_add(amount));
+			Self::deposit_event(RawEvent::PayoutFailed(who.clone(), amount));
+		}
+	}
+
+	/// Passes the stakes of the votes forfeited in a challenge to the slash handler. Those stakes
+	/// are taken off their voters' `TotalStaked` as the votes are claimed or swept. The withdrawal
+	/// can only fail if the pot holds less than it owes, in which case the stakes are left in it.
+	fn slash_forfeited_votes(amount: BalanceOf<T, I>) {
+		if amount.is_zero() {
+			return;
+		}
+		let slashed = T::Currency::withdraw(
+			&Self::account_id(),
+			amount,
+			WithdrawReason::Transfer.into(),
+			ExistenceRequirement::KeepAlive,
+		);
+		if let Ok(slashed) = slashed {
+			T::Slash::on_unbalanced(slashed);
+		}
+	}
+
+	/// Whether a vote in a resolved challenge was forfeited: left unrevealed, or on the losing side
+	/// of a challenge that reached the quorum.
+	fn is_forfeited(challenge: &ChallengeDetailOf<T, I>, vote: &VoteOf<T, I>) -> bool {
+		match vote.aye_or_nay {
+			Some(aye_or_nay) => challenge.quorum_met && challenge.outcome != Some(aye_or_nay),
+			None => true,
+		}
+	}
+
+	/// Removes a claimed or swept vote, along with its entry in the voter's `CastVotes`.
+	fn drop_vote(challenge_id: ChallengeId, voter: &AccountIdOf<T>) {
+		<Votes<T, I>>::remove(challenge_id, voter);
+		Self::unindex_cast_vote(voter, challenge_id);
+	}
+
+	fn deduct_total_staked(who: &AccountIdOf<T>, amount: BalanceOf<T, I>) {
+		let total = <TotalStaked<T, I>>::get(who).saturating_sub(amount);
+		if total.is_zero() {
+			<TotalStaked<T, I>>::remove(who);
+		}
+		else {
+			<TotalStaked<T, I>>::insert(who, total);
+		}
+	}
+
+	fn promote_application(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, listing: &mut ListingDetailOf<T, I>) {
 
 			// Mutate the listing, and make the promotion
 			listing.application_expiry = None;
 			listing.in_registry = true;
-			<Listings<T>>::insert(&listing_id, listing);
+			<Listings<T, I>>::insert(registry_id, &listing_id, listing);
+			Self::unindex_application(registry_id, listing_id);
+			Self::index_registered(registry_id, listing_id);
 
 			// Raise the event
-			Self::deposit_event(RawEvent::Accepted(listing_id));
+			Self::deposit_event(RawEvent::Accepted(registry_id, listing_id));
 	}
 
-	fn settle_challenge(listing_id: ListingIdOf<T>, listing: &mut ListingDetailOf<T>) {
+	fn complete_exit(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, listing: &ListingDetailOf<T, I>) {
+
+		// Release the owner's deposit and remove the listing
+		Self::release_stake_or_owe(&listing.owner, listing.deposit);
+		Self::remove_listing(registry_id, listing_id, listing);
+
+		// Raise the event
+		Self::deposit_event(RawEvent::Exited(registry_id, listing_id));
+	}
+
+	fn settle_challenge(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, listing: &mut ListingDetailOf<T, I>) {
 
 		// Note whether the listing was previously registered, for event emission
 		// (if not, it is a challenged application)
 		let previously_registered = listing.in_registry;
 
 		// Lookup challenge and count the vote
-		let challenge_id = Listings::<T>::get(listing_id).challenge_id.expect("Confirmed a challenge existed before calling; qed");
-		let challenge = Challenges::<T>::get(challenge_id);
-		let listing_is_good = challenge.total_aye > challenge.total_nay;
+		let challenge_id = Listings::<T, I>::get(registry_id, listing_id).challenge_id.expect("Confirmed a challenge existed before calling; qed");
+		let mut challenge = Challenges::<T, I>::get(challenge_id);
+		Self::unindex_opened_challenge(&challenge.owner, challenge_id);
+
+		// Only the stake of revealed votes counts towards the quorum, not the parties' deposits
+		let total = challenge.total_aye.saturating_add(challenge.total_nay);
+		let turnout = total.saturating_sub(listing.deposit).saturating_sub(challenge.deposit);
+		if turnout < T::VoteQuorum::get() {
+			Self::void_challenge(registry_id, listing_id, listing, challenge_id, challenge, turnout);
+			return;
+		}
 
-		Self::deposit_event(RawEvent::Resolved(challenge.listing_id.clone(), listing_is_good));
-		if listing_is_good {
-			// slash challenger's deposit
-			T::Currency::unreserve(&challenge.owner, challenge.deposit);
-			T::Currency::slash(&challenge.owner, challenge.deposit);
+		// The listing is kept if the aye side has at least `PassThreshold` of the vote
+		let listing_is_good = Perbill::from_rational_approximation(challenge.total_aye, total) >= T::PassThreshold::get();
+
+		Self::deposit_event(RawEvent::Resolved(registry_id, challenge.listing_id.clone(), listing_is_good));
+
+		// Forfeit the losing party's deposit, and note who won.
+		// The winning stake is the winning side's tally without the winning party's deposit.
+		let (winner, losing_deposit, winning_stake) = if listing_is_good {
+			// forfeit challenger's deposit
+			Self::forfeit_stake(&challenge.owner, challenge.deposit);
 
 			// add item to registry
 			listing.in_registry = true;
 			listing.challenge_id = None;
-			Listings::<T>::insert(listing_id, listing);
+			Listings::<T, I>::insert(registry_id, listing_id, &*listing);
 
 			// Emit event for newly-registered listings
 			if !previously_registered {
-				Self::deposit_event(RawEvent::Accepted(challenge.listing_id));
+				Self::unindex_application(registry_id, listing_id);
+				Self::index_registered(registry_id, listing_id);
+				Self::deposit_event(RawEvent::Accepted(registry_id, challenge.listing_id));
 			}
 
+			(listing.owner.clone(), challenge.deposit, challenge.total_aye.saturating_sub(listing.deposit))
 		} else {
-			// slash owner's deposit
-			T::Currency::unreserve(&listing.owner, listing.deposit);
-			T::Currency::slash(&listing.owner, listing.deposit);
+			// forfeit owner's deposit
+			Self::forfeit_stake(&listing.owner, listing.deposit);
 
 			// release challenger's deposit
-			T::Currency::unreserve(&challenge.owner, challenge.deposit);
+			Self::release_stake_or_owe(&challenge.owner, challenge.deposit);
 
 			// remove item from registry
 			listing.in_registry = false;
-			Listings::<T>::remove(&challenge.listing_id);
+			Self::remove_listing(registry_id, listing_id, listing);
 
 			// Emit event for newly de-registered listings
 			if previously_registered {
-				Self::deposit_event(RawEvent::Rejected(challenge.listing_id));
+				Self::deposit_event(RawEvent::Rejected(registry_id, challenge.listing_id));
 			}
+
+			(challenge.owner.clone(), listing.deposit, challenge.total_nay.saturating_sub(challenge.deposit))
+		};
+
+		// The winning party gets its share of the losing deposit right away. The rest stays in the
+		// pot, for the winning voters to claim. If nobody voted with the winner, it gets everything.
+		let (winner_reward, reward_pool) = if winning_stake.is_zero() {
+			(losing_deposit, Zero::zero())
+		} else {
+			let winner_reward = T::DispensationPct::get() * losing_deposit;
+			(winner_reward, losing_deposit - winner_reward)
+		};
+		Self::pay_out_or_owe(&winner, winner_reward);
+
+		// Losing and unrevealed stakes go to the slash handler right away
+		Self::slash_forfeited_votes(challenge.committed_stake.saturating_sub(winning_stake));
+
+		// Record the outcome. Winning voters claim their stakes and rewards with `claim_reward`, and
+		// the challenge is dropped once they all have.
+		challenge.vote_count = if listing_is_good { challenge.aye_votes } else { challenge.nay_votes };
+		challenge.outcome = Some(listing_is_good);
+		challenge.reward_pool = reward_pool;
+		challenge.winning_stake = winning_stake;
+		challenge.quorum_met = true;
+		Self::unindex_open_challenge(challenge_id);
+		Self::archive_outcome(challenge_id, &challenge);
+		Self::store_resolved_challenge(challenge_id, challenge);
+
+		if listing_is_good {
+			Self::complete_pending_exit(registry_id, listing_id, listing);
 		}
+	}
 
-		// Loop through votes releasing or slashing as necessary
-		for vote in challenge.votes.iter() {
-			T::Currency::unreserve(&vote.voter, vote.deposit);
-			if vote.aye_or_nay != listing_is_good {
-				T::Currency::slash(&vote.voter, vote.deposit);
+	/// Resolves a challenge that did not reach the vote quorum. The status quo wins and no deposit
+	/// is forfeited: a registered listing stays in the registry, and a challenged application goes
+	/// back to its apply stage, to be accepted at its original expiry, or right away if that has
+	/// passed. A challenge thus cannot cut an apply stage short.
+	/// Only the stakes of unrevealed votes are forfeited, `revealed_stake` being those of the others.
+	fn void_challenge(
+		registry_id: RegistryId,
+		listing_id: ListingIdOf<T, I>,
+		listing: &mut ListingDetailOf<T, I>,
+		challenge_id: ChallengeId,
+		mut challenge: ChallengeDetailOf<T, I>,
+		revealed_stake: BalanceOf<T, I>,
+	) {
+		Self::deposit_event(RawEvent::QuorumNotReached(registry_id, listing_id, challenge_id));
+		Self::deposit_event(RawEvent::Resolved(registry_id, listing_id, true));
+
+		// release challenger's deposit
+		Self::release_stake_or_owe(&challenge.owner, challenge.deposit);
+
+		listing.challenge_id = None;
+		let now = <system::Module<T>>::block_number();
+		match challenge.application_expiry {
+			Some(expiry) if !listing.in_registry && expiry > now => {
+				// The update scheduled at the original expiry is still in `ListingsToUpdate`
+				listing.application_expiry = Some(expiry);
+				Listings::<T, I>::insert(registry_id, listing_id, &*listing);
 			}
+			_ if !listing.in_registry => Self::promote_application(registry_id, listing_id, listing),
+			_ => Listings::<T, I>::insert(registry_id, listing_id, &*listing),
+		}
+		Self::slash_forfeited_votes(challenge.committed_stake.saturating_sub(revealed_stake));
+
+		// Record the outcome. Voters get their revealed stakes back with `claim_reward`, and the
+		// challenge is dropped once they all have.
+		challenge.vote_count = challenge.aye_votes + challenge.nay_votes;
+		challenge.outcome = Some(true);
+		challenge.quorum_met = false;
+		Self::unindex_open_challenge(challenge_id);
+		Self::archive_outcome(challenge_id, &challenge);
+		Self::store_resolved_challenge(challenge_id, challenge);
+
+		Self::complete_pending_exit(registry_id, listing_id, listing);
+	}
+
+	/// Records the outcome of a challenge that was just resolved in `ChallengeOutcomes`,
+	/// and schedules its removal after `T::OutcomeRetention` blocks.
+	fn archive_outcome(challenge_id: ChallengeId, challenge: &ChallengeDetailOf<T, I>) {
+		let now = <system::Module<T>>::block_number();
+		let outcome = ChallengeOutcomeOf::<T, I> {
+			registry_id: challenge.registry_id,
+			listing_id: challenge.listing_id,
+			challenger: challenge.owner.clone(),
+			total_aye: challenge.total_aye,
+			total_nay: challenge.total_nay,
+			listing_kept: challenge.outcome == Some(true),
+			quorum_met: challenge.quorum_met,
+			resolved_at: now,
+		};
+		ChallengeOutcomes::<T, I>::insert(challenge_id, outcome);
+
+		let retention = T::OutcomeRetention::get();
+		if !retention.is_zero() {
+			<OutcomesToPrune<T, I>>::append_or_insert(now.saturating_add(retention), &vec![challenge_id]);
+		}
+	}
+
+	/// Stores a resolved challenge while some of its winning or undecided votes are yet to be
+	/// claimed, and drops it from `Challenges` otherwise. Forfeited votes do not hold it back.
+	fn store_resolved_challenge(challenge_id: ChallengeId, challenge: ChallengeDetailOf<T, I>) {
+		if challenge.vote_count == 0 {
+			Challenges::<T, I>::remove(challenge_id);
+			Claimed::<T, I>::remove_prefix(challenge_id);
+		}
+		else {
+			Challenges::<T, I>::insert(challenge_id, challenge);
+		}
+	}
+
+	/// Completes an exit that was held back by a challenge, if it is due.
+	fn complete_pending_exit(registry_id: RegistryId, listing_id: ListingIdOf<T, I>, listing: &ListingDetailOf<T, I>) {
+		let now = <system::Module<T>>::block_number();
+		if listing.exit_expiry.map_or(false, |exit_expiry| exit_expiry <= now) {
+			Self::complete_exit(registry_id, listing_id, listing);
 		}
 	}
 }
@@ -1,13 +1,26 @@
 use super::*;
 
 use sp_core::H256;
-use sp_runtime::{Perbill, traits::{BlakeTwo256, IdentityLookup, OnFinalize}, testing::Header};
-use frame_support::{impl_outer_origin, assert_ok, assert_noop, parameter_types, weights::Weight};
+use sp_runtime::{ModuleId, Perbill, Percent, traits::{BlakeTwo256, IdentityLookup, OnFinalize}, testing::Header};
+use frame_support::{impl_outer_event, impl_outer_origin, assert_ok, assert_noop, parameter_types, weights::Weight};
+use std::cell::Cell;
 
 impl_outer_origin! {
 	pub enum Origin for Test {}
 }
 
+mod tcr {
+	pub use crate::{Event, Instance1, Instance2};
+}
+
+impl_outer_event! {
+	pub enum TestEvent for Test {
+		tcr<T>,
+		tcr Instance1<T>,
+		tcr Instance2<T>,
+	}
+}
+
 // For testing the module, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of modules we want to use.
@@ -29,7 +42,7 @@ impl system::Trait for Test {
 	type AccountId = u64;
 	type Lookup = IdentityLookup<Self::AccountId>;
 	type Header = Header;
-	type Event = ();
+	type Event = TestEvent;
 	type BlockHashCount = BlockHashCount;
 	type MaximumBlockWeight = MaximumBlockWeight;
 	type AvailableBlockRatio = AvailableBlockRatio;
@@ -54,20 +67,154 @@ impl balances::Trait for Test {
 	type TransferPayment = ();
 }
 parameter_types! {
-	pub const MinDeposit: u64 = 100;
-	pub const ApplyStageLen: u64 = 10;
-	pub const CommitStageLen: u64 = 10;
+	pub const TcrModuleId: ModuleId = ModuleId(*b"py/tcreg");
+	pub const TokensModuleId: ModuleId = ModuleId(*b"tc/tokns");
+	pub const MembersModuleId: ModuleId = ModuleId(*b"mb/membr");
+	pub const DispensationPct: Percent = Percent::from_percent(50);
+	pub const ExitDelay: u64 = 5;
+	pub const MaxListingDataLen: u32 = 16;
+	pub const OutcomeRetention: u64 = 50;
+	pub const RegistryDeposit: u64 = 500;
+	pub const MinDepositFloor: u64 = 10;
+	pub const MinStageLen: u64 = 1;
+	pub const TipShare: Percent = Percent::from_percent(10);
+	pub const UpdateTip: u64 = 10;
+	pub const MinVoteStake: u64 = 10;
+}
+parameter_types! {
+	pub const PassThreshold: Perbill = Perbill::from_percent(50);
+}
+thread_local! {
+	static CONTENT_ADDRESSED: Cell<bool> = Cell::new(false);
+	static VOTE_QUORUM: Cell<u64> = Cell::new(0);
+	static SETTLEMENT_BUDGET: Cell<Weight> = Cell::new(weights::SCHEDULE + 100 * weights::update());
+	static SLASH_TO: Cell<SlashTo> = Cell::new(SlashTo::Pot);
+}
+// A vote quorum that can be changed per test.
+pub struct VoteQuorum;
+impl Get<u64> for VoteQuorum {
+	fn get() -> u64 {
+		VOTE_QUORUM.with(|v| v.get())
+	}
+}
+// A settlement budget that can be changed per test. By default, 100 unchallenged listings are updated per block.
+pub struct SettlementBudget;
+impl Get<Weight> for SettlementBudget {
+	fn get() -> Weight {
+		SETTLEMENT_BUDGET.with(|v| v.get())
+	}
+}
+// Where slashed funds go, which can be changed per test. By default, they stay in the pot.
+// `Split` is the node's configuration: a share for tips, and the rest for the treasury.
+#[derive(Clone, Copy)]
+enum SlashTo {
+	Pot,
+	Treasury,
+	Burn,
+	Split,
+}
+const TREASURY: u64 = 5;
+pub struct ToTreasury;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for ToTreasury {
+	fn on_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		Balances::resolve_creating(&TREASURY, amount);
+	}
+}
+pub struct TestSlash;
+impl OnUnbalanced<NegativeImbalanceOf<Test>> for TestSlash {
+	fn on_unbalanced(amount: NegativeImbalanceOf<Test>) {
+		match SLASH_TO.with(|v| v.get()) {
+			SlashTo::Pot => ToPot::<Test>::on_unbalanced(amount),
+			SlashTo::Treasury => ToTreasury::on_unbalanced(amount),
+			SlashTo::Burn => drop(amount),
+			SlashTo::Split => SplitToPot::<Test, TipShare, ToTreasury>::on_unbalanced(amount),
+		}
+	}
+}
+// Content addressing that can be switched on per test. Listing ids are the first bytes of the hash.
+pub struct TestListingIds;
+impl ListingIds<H256, u32> for TestListingIds {
+	fn from_data_hash(hash: H256) -> Option<u32> {
+		if CONTENT_ADDRESSED.with(|c| c.get()) {
+			let mut id = [0u8; 4];
+			id.copy_from_slice(&hash.as_bytes()[..4]);
+			Some(u32::from_le_bytes(id))
+		} else {
+			None
+		}
+	}
 }
 impl Trait for Test {
-	type Event = ();
+	type Event = TestEvent;
 	type ListingId = u32;
 	type Currency = balances::Module<Self>;
+	type ParamOrigin = system::EnsureRoot<u64>;
+	type ModuleId = TcrModuleId;
+
+	type DispensationPct = DispensationPct;
+	type VoteQuorum = VoteQuorum;
+	type MinVoteStake = MinVoteStake;
+	type PassThreshold = PassThreshold;
+	type ExitDelay = ExitDelay;
+	type MaxListingDataLen = MaxListingDataLen;
+	type ListingIds = TestListingIds;
+	type OutcomeRetention = OutcomeRetention;
+	type SettlementBudget = SettlementBudget;
+	type UpdateTip = UpdateTip;
+	type RegistryDeposit = RegistryDeposit;
+	type MinDepositFloor = MinDepositFloor;
+	type MinStageLen = MinStageLen;
+	type Slash = TestSlash;
+}
+// A second registry, of content-addressed token listings.
+impl Trait<Instance1> for Test {
+	type Event = TestEvent;
+	type ListingId = H256;
+	type Currency = balances::Module<Self>;
+	type ParamOrigin = system::EnsureRoot<u64>;
+	type ModuleId = TokensModuleId;
+
+	type DispensationPct = DispensationPct;
+	type VoteQuorum = VoteQuorum;
+	type MinVoteStake = MinVoteStake;
+	type PassThreshold = PassThreshold;
+	type ExitDelay = ExitDelay;
+	type MaxListingDataLen = MaxListingDataLen;
+	type ListingIds = ContentAddressed;
+	type OutcomeRetention = OutcomeRetention;
+	type SettlementBudget = SettlementBudget;
+	type UpdateTip = UpdateTip;
+	type RegistryDeposit = RegistryDeposit;
+	type MinDepositFloor = MinDepositFloor;
+	type MinStageLen = MinStageLen;
+	type Slash = ();
+}
+// A third registry, of accounts, that other modules can gate on.
+impl Trait<Instance2> for Test {
+	type Event = TestEvent;
+	type ListingId = u64;
+	type Currency = balances::Module<Self>;
+	type ParamOrigin = system::EnsureRoot<u64>;
+	type ModuleId = MembersModuleId;
 
-	type MinDeposit = MinDeposit;
-	type ApplyStageLen = ApplyStageLen;
-	type CommitStageLen = CommitStageLen;
+	type DispensationPct = DispensationPct;
+	type VoteQuorum = VoteQuorum;
+	type MinVoteStake = MinVoteStake;
+	type PassThreshold = PassThreshold;
+	type ExitDelay = ExitDelay;
+	type MaxListingDataLen = MaxListingDataLen;
+	type ListingIds = ();
+	type OutcomeRetention = OutcomeRetention;
+	type SettlementBudget = SettlementBudget;
+	type UpdateTip = UpdateTip;
+	type Slash = ();
+	type RegistryDeposit = RegistryDeposit;
+	type MinDepositFloor = MinDepositFloor;
+	type MinStageLen = MinStageLen;
 }
 type Tcr = Module<Test>;
+type Tokens = Module<Test, Instance1>;
+type Members = Module<Test, Instance2>;
 type System = system::Module<Test>;
 type Balances = balances::Module<Test>;
 
@@ -85,16 +232,62 @@ fn new_test_ext() -> sp_io::TestExternalities {
 		],
 		vesting: vec![],
 	}.assimilate_storage(&mut t).unwrap();
+	let _ = GenesisConfig::<Test>{
+		min_deposit: 100,
+		apply_stage_len: 10,
+		commit_stage_len: 10,
+		reveal_stage_len: 10,
+	}.assimilate_storage(&mut t).unwrap();
+	let _ = GenesisConfig::<Test, Instance1>{
+		min_deposit: 50,
+		apply_stage_len: 5,
+		commit_stage_len: 5,
+		reveal_stage_len: 5,
+	}.assimilate_storage(&mut t).unwrap();
+	let _ = GenesisConfig::<Test, Instance2>{
+		min_deposit: 100,
+		apply_stage_len: 10,
+		commit_stage_len: 10,
+		reveal_stage_len: 10,
+	}.assimilate_storage(&mut t).unwrap();
 
 	t.into()
 }
 
+// A 32-byte salt, derived from a number for readability.
+fn salt(n: u64) -> [u8; 32] {
+	let mut salt = [0u8; 32];
+	salt[..8].copy_from_slice(&n.to_le_bytes());
+	salt
+}
+
+// The commitment a voter submits in a challenge for a given vote and salt.
+fn commitment(voter: u64, challenge_id: ChallengeId, vote_bool: bool, salt_n: u64) -> H256 {
+	BlakeTwo256::hash_of(&(voter, challenge_id, vote_bool, salt(salt_n)))
+}
+
+// The pot's balance on top of the existential deposit it is created with. Each instance has
+// a pot, so the total issuance includes three existential deposits.
+fn pot() -> u64 {
+	Balances::free_balance(Tcr::account_id()) - Balances::minimum_balance()
+}
+
+// The combined balance of all test accounts and the pot, without the pot's existential deposit.
+fn total_balance() -> u64 {
+	(1..=4).map(|who| Balances::total_balance(&who)).sum::<u64>() + pot()
+}
+
+// The total stake held in the pot for the test accounts.
+fn total_staked() -> u64 {
+	(1..=4).map(Tcr::total_staked).sum()
+}
+
 #[test]
 fn should_fail_low_deposit() {
 	new_test_ext().execute_with(|| {
 		assert_noop!(
-			Tcr::propose(Origin::signed(1), 1, 99),
-			"deposit should be more than min_deposit"
+			Tcr::propose(Origin::signed(1), 0, Some(1), 99, vec![]),
+			Error::<Test, DefaultInstance>::DepositTooLow
 		);
 	});
 }
@@ -105,13 +298,15 @@ fn should_pass_propose() {
 		// Make the proposal
 		assert_ok!(Tcr::propose(
 			Origin::signed(1),
-			1,
-			100
+			0,
+			Some(1),
+			100,
+			vec![]
 		));
 
-		// Ensure the proper balance has been reserved
+		// Ensure the proper balance has been staked
 		assert_eq!(Balances::free_balance(1), 999_900);
-		assert_eq!(Balances::reserved_balance(1), 100);
+		assert_eq!(Tcr::total_staked(1), 100);
 	});
 }
 
@@ -120,12 +315,14 @@ fn should_fail_challenge_same_owner() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(Tcr::propose(
 			Origin::signed(1),
-			1,
-			101
+			0,
+			Some(1),
+			101,
+			vec![]
 		));
 		assert_noop!(
-			Tcr::challenge(Origin::signed(1), 1, 100),
-			"You cannot challenge your own listing."
+			Tcr::challenge(Origin::signed(1), 0, 1, 100),
+			Error::<Test, DefaultInstance>::CannotChallengeOwnListing
 		);
 	});
 }
@@ -135,10 +332,12 @@ fn should_pass_challenge() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(Tcr::propose(
 			Origin::signed(1),
-			1,
-			101
+			0,
+			Some(1),
+			101,
+			vec![]
 		));
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 101));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 101));
 	});
 }
 
@@ -146,57 +345,136 @@ fn should_pass_challenge() {
 fn promotion_works() {
 	new_test_ext().execute_with(|| {
 
-			assert_ok!(Tcr::propose(Origin::signed(1), 1, 101));
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 101, vec![]));
 			System::set_block_number(11);
 			Tcr::on_finalize(11);
-			assert!(Tcr::listings(1).in_registry);
+			assert!(Tcr::listings(0, 1).in_registry);
 	});
 }
 
 #[test]
 fn aye_vote_works_correctly() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Tcr::propose(Origin::signed(1), 1, 100));
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 300));
-		assert_ok!(Tcr::vote(Origin::signed(1), 1, true, 50));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(1), 0, 1, commitment(1, 0, true, 42), 50));
+
+		// Committed votes are not counted yet
+		assert_eq!(Tcr::challenges(0).total_aye, 100);
+
+		// Reveal during the reveal stage
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(1), 0, 1, true, salt(42)));
 
 		// Ensure the challenges struct has been updated properly
 		assert_eq!(Tcr::challenges(0).total_aye, 100 + 50);
 		assert_eq!(Tcr::challenges(0).total_nay, 300);
 
-		// Ensure the proper balances have been reserved
-		assert_eq!(Balances::reserved_balance(1), 100 + 50);
-		assert_eq!(Balances::reserved_balance(2), 300);
+		// Ensure the proper balances have been staked
+		assert_eq!(Tcr::total_staked(1), 100 + 50);
+		assert_eq!(Tcr::total_staked(2), 300);
 	});
 }
 
 #[test]
 fn nay_vote_works_correctly() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Tcr::propose(Origin::signed(1), 1, 100));
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 300));
-		assert_ok!(Tcr::vote(Origin::signed(3), 1, false, 50));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 7), 50));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(7)));
 
 		// Ensure challenges struct update properly
 		assert_eq!(Tcr::challenges(0).total_aye, 100);
 		assert_eq!(Tcr::challenges(0).total_nay, 300 + 50);
 
-		// Ensure balances reserved properly
-		assert_eq!(Balances::reserved_balance(1), 100);
-		assert_eq!(Balances::reserved_balance(2), 300);
-		assert_eq!(Balances::reserved_balance(3), 50);
+		// Ensure balances staked properly
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Tcr::total_staked(2), 300);
+		assert_eq!(Tcr::total_staked(3), 50);
 });
 }
 
+#[test]
+fn commit_and_reveal_respect_stages() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 50));
+
+		// Cannot commit twice, or reveal during the commit stage
+		assert_noop!(
+			Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 50),
+			Error::<Test, DefaultInstance>::AlreadyVoted
+		);
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)),
+			Error::<Test, DefaultInstance>::RevealStageNotStarted
+		);
+
+		// Cannot commit during the reveal stage, or reveal something else than committed
+		System::set_block_number(11);
+		assert_noop!(
+			Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, true, 1), 50),
+			Error::<Test, DefaultInstance>::CommitStageEnded
+		);
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(1)),
+			Error::<Test, DefaultInstance>::CommitmentMismatch
+		);
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(2)),
+			Error::<Test, DefaultInstance>::CommitmentMismatch
+		);
+
+		// Cannot reveal after the reveal stage
+		System::set_block_number(21);
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)),
+			Error::<Test, DefaultInstance>::RevealStageEnded
+		);
+	});
+}
+
+#[test]
+fn commitments_are_bound_to_voter_and_challenge() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+
+		// Voter 4 copies voter 3's commitment, and voter 3 reuses it in another challenge
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(3, 0, true, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 2, commitment(3, 0, true, 1), 100));
+
+		// Once the salt is public, only the original vote can be revealed with it
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(4), 0, 1, true, salt(1)),
+			Error::<Test, DefaultInstance>::CommitmentMismatch
+		);
+		assert_noop!(
+			Tcr::reveal_vote(Origin::signed(3), 0, 2, true, salt(1)),
+			Error::<Test, DefaultInstance>::CommitmentMismatch
+		);
+	});
+}
+
 #[test]
 fn successfully_challenged_proposals_are_removed() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(Tcr::propose(Origin::signed(1), 1, 100));
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 300));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
 
-		Tcr::on_finalize(11);
+		Tcr::on_finalize(20);
 
-		assert!(!Tcr::registry_contains(1))
+		assert!(!Tcr::registry_contains(0, 1));
+		assert!(!<Listings<Test>>::exists(0, 1));
 	});
 }
 
@@ -205,7 +483,7 @@ fn successfully_challenged_listings_are_removed() {
 	new_test_ext().execute_with(|| {
 
 		// Propose
-		assert_ok!(Tcr::propose(Origin::signed(1), 1, 100));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
 
 		// Promote
 		System::set_block_number(11);
@@ -213,12 +491,12 @@ fn successfully_challenged_listings_are_removed() {
 
 		// Challenge
 		System::set_block_number(12);
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 300));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
 
-		// Run on_finalize
-		Tcr::on_finalize(22);
+		// Run on_finalize at the end of the reveal stage
+		Tcr::on_finalize(32);
 
-		assert!(!Tcr::registry_contains(1))
+		assert!(!Tcr::registry_contains(0, 1))
 	});
 }
 
@@ -226,7 +504,7 @@ fn successfully_challenged_listings_are_removed() {
 fn unsuccessfully_challenged_listings_are_kept() {
 	new_test_ext().execute_with(|| {
 		// Propose
-		assert_ok!(Tcr::propose(Origin::signed(1), 1, 100));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
 
 		// Promote
 		System::set_block_number(11);
@@ -234,15 +512,1302 @@ fn unsuccessfully_challenged_listings_are_kept() {
 
 		// Challenge
 		System::set_block_number(12);
-		assert_ok!(Tcr::challenge(Origin::signed(2), 1, 300));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
 
 		// Aye vote saves listing
-		assert_ok!(Tcr::vote(Origin::signed(3), 1, true, 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 5), 400));
+		System::set_block_number(23);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(5)));
 
-		// Run on_finalize
-		Tcr::on_finalize(22);
+		// Run on_finalize at the end of the reveal stage
+		Tcr::on_finalize(32);
 
 		// Ensure listing is still in the registry
-		assert!(Tcr::registry_contains(1))
+		assert!(Tcr::registry_contains(0, 1))
+	});
+}
+
+#[test]
+fn unrevealed_votes_are_not_counted() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		System::set_block_number(12);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		// A large aye vote that is never revealed
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 5), 400));
+
+		Tcr::on_finalize(32);
+
+		// The listing loses, and the unrevealed voter's stake is slashed right away
+		assert!(!Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::pot_surplus(), 400);
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_eq!(Tcr::total_staked(3), 0);
+		assert_eq!(Balances::free_balance(3), 1000_000 - 400);
+	});
+}
+
+#[test]
+fn hiding_a_losing_vote_is_no_better_than_revealing_it() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, true, 2), 100));
+
+		// Only voter 3 reveals, and the aye side loses anyway
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		Tcr::on_finalize(20);
+		assert!(!Tcr::registry_contains(0, 1));
+
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_ok!(Tcr::claim_reward(Origin::signed(4), 0));
+		assert_eq!(Balances::free_balance(3), 1000_000 - 100);
+		assert_eq!(Balances::free_balance(4), Balances::free_balance(3));
+		assert_eq!(Tcr::total_staked(4), 0);
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn failed_challenge_rewards_owner_and_aye_voters() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 100));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(1)));
+
+		Tcr::on_finalize(20);
+		assert!(Tcr::registry_contains(0, 1));
+
+		// Owner gets half of the challenger's deposit and keeps the listing deposit staked
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100 + 150);
+		assert_eq!(Tcr::total_staked(1), 100);
+		// Challenger loses their deposit
+		assert_eq!(Balances::free_balance(2), 1000_000 - 300);
+		assert_eq!(Tcr::total_staked(2), 0);
+		// The other half waits in the pot, next to the listing deposit and voters' stakes
+		// and the nay voter's stake, slashed to the pot's surplus
+		assert_eq!(pot(), 100 + 150 + 400 + 100);
+		assert_eq!(Tcr::pot_surplus(), 100);
+		assert_eq!(Tcr::total_staked(3), 400);
+		assert_eq!(total_balance(), 4 * 1000_000);
+
+		// The only aye voter claims the other half
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_eq!(Balances::free_balance(3), 1000_000 + 150);
+		assert_eq!(Tcr::total_staked(3), 0);
+
+		// Claiming the nay vote only drops it
+		assert_ok!(Tcr::claim_reward(Origin::signed(4), 0));
+		assert_eq!(Balances::free_balance(4), 1000_000 - 100);
+		assert_eq!(Tcr::total_staked(4), 0);
+		assert_eq!(pot(), 100 + 100);
+
+		assert_eq!(total_balance(), 4 * 1000_000);
+		assert_eq!(Balances::total_issuance(), 4 * 1000_000 + 3 * Balances::minimum_balance());
+	});
+}
+
+#[test]
+fn successful_challenge_rewards_challenger_and_nay_voters_pro_rata() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 800, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 800));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 2), 300));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(2)));
+
+		Tcr::on_finalize(20);
+		assert!(!Tcr::registry_contains(0, 1));
+
+		// Owner loses the listing deposit
+		assert_eq!(Balances::free_balance(1), 1000_000 - 800);
+		assert_eq!(Tcr::total_staked(1), 0);
+		// Challenger gets their deposit back, plus half of the owner's deposit
+		assert_eq!(Balances::free_balance(2), 1000_000 + 400);
+		assert_eq!(Tcr::total_staked(2), 0);
+
+		// Nay voters split the other half by stake
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_ok!(Tcr::claim_reward(Origin::signed(4), 0));
+		assert_eq!(Balances::free_balance(3), 1000_000 + 100);
+		assert_eq!(Balances::free_balance(4), 1000_000 + 300);
+		assert_eq!(pot(), 0);
+
+		assert_eq!(total_balance(), 4 * 1000_000);
+		assert_eq!(Balances::total_issuance(), 4 * 1000_000 + 3 * Balances::minimum_balance());
+	});
+}
+
+#[test]
+fn rewards_round_down_and_never_overpay_the_pool() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		for &voter in &[1, 3, 4] {
+			assert_ok!(Tcr::commit_vote(Origin::signed(voter), 0, 1, commitment(voter, 0, true, voter), 100));
+		}
+
+		System::set_block_number(11);
+		for &voter in &[1, 3, 4] {
+			assert_ok!(Tcr::reveal_vote(Origin::signed(voter), 0, 1, true, salt(voter)));
+		}
+		Tcr::on_finalize(20);
+		assert_eq!(Tcr::challenges(0).reward_pool, 50);
+
+		// A third of 50 is paid rounded down, and each claim is a share of what is left
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_eq!(Balances::free_balance(3), 1000_000 + 16);
+		assert_eq!(Tcr::challenges(0).reward_pool, 34);
+		assert_ok!(Tcr::claim_reward(Origin::signed(4), 0));
+		assert_eq!(Balances::free_balance(4), 1000_000 + 17);
+
+		// The last claimant gets the remainder, which empties the pool exactly
+		assert_ok!(Tcr::claim_reward(Origin::signed(1), 0));
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100 + 50 + 17);
+		assert_eq!(pot(), total_staked());
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn winning_party_gets_whole_pool_without_winning_voters() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		Tcr::on_finalize(20);
+
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100);
+		assert_eq!(Balances::free_balance(2), 1000_000 + 100);
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn claim_reward_checks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 2), 100));
+
+		// Nothing to claim before the challenge is resolved
+		assert_noop!(Tcr::claim_reward(Origin::signed(3), 1), Error::<Test, DefaultInstance>::ChallengeNotFound);
+		assert_noop!(Tcr::claim_reward(Origin::signed(3), 0), Error::<Test, DefaultInstance>::ChallengeNotResolved);
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(2)));
+		Tcr::on_finalize(20);
+
+		// Only voters can claim, and only once
+		assert_noop!(Tcr::claim_reward(Origin::signed(1), 0), Error::<Test, DefaultInstance>::VoteNotFound);
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert!(Tcr::claimed(0, 3));
+		assert_noop!(Tcr::claim_reward(Origin::signed(3), 0), Error::<Test, DefaultInstance>::AlreadyClaimed);
+	});
+}
+
+#[test]
+fn exit_checks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+
+		// Applications cannot exit
+		assert_noop!(Tcr::exit(Origin::signed(1), 0, 1), Error::<Test, DefaultInstance>::NotInRegistry);
+
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		// Only the owner can exit, and only once
+		assert_noop!(Tcr::exit(Origin::signed(2), 0, 1), Error::<Test, DefaultInstance>::NotListingOwner);
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+		assert_noop!(Tcr::exit(Origin::signed(1), 0, 1), Error::<Test, DefaultInstance>::ExitAlreadyInitiated);
+	});
+}
+
+#[test]
+fn exit_completes_after_delay() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		System::set_block_number(12);
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+
+		// Still listed during the exit delay
+		Tcr::on_finalize(16);
+		assert!(Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::total_staked(1), 100);
+
+		Tcr::on_finalize(17);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert!(!<Listings<Test>>::exists(0, 1));
+		assert_eq!(Tcr::total_staked(1), 0);
+		assert_eq!(Balances::free_balance(1), 1000_000);
+	});
+}
+
+#[test]
+fn exiting_listing_can_still_be_challenged() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		System::set_block_number(12);
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+
+		// Challenge during the exit delay
+		System::set_block_number(13);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		// The exit is held back by the challenge
+		Tcr::on_finalize(17);
+		assert!(Tcr::registry_contains(0, 1));
+
+		// The listing loses, and the owner's deposit is forfeited rather than returned
+		Tcr::on_finalize(33);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::total_staked(1), 0);
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100);
+	});
+}
+
+#[test]
+fn surviving_listing_exits_after_challenge() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		System::set_block_number(12);
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+
+		System::set_block_number(13);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 100));
+		System::set_block_number(24);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+
+		// The listing survives the challenge, and the pending exit completes
+		System::set_block_number(33);
+		Tcr::on_finalize(33);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert!(!<Listings<Test>>::exists(0, 1));
+		assert_eq!(Tcr::total_staked(1), 0);
+		assert_eq!(Balances::free_balance(1), 1000_000 + 50);
+	});
+}
+
+#[test]
+fn deposit_more_raises_challenge_threshold() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_noop!(
+			Tcr::deposit_more(Origin::signed(2), 0, 1, 50),
+			Error::<Test, DefaultInstance>::NotListingOwner
+		);
+		assert_ok!(Tcr::deposit_more(Origin::signed(1), 0, 1, 50));
+
+		assert_eq!(Tcr::listings(0, 1).deposit, 150);
+		assert_eq!(Tcr::total_staked(1), 150);
+
+		// Challengers must now match the larger deposit
+		assert_noop!(Tcr::challenge(Origin::signed(2), 0, 1, 100), Error::<Test, DefaultInstance>::ChallengeDepositTooLow);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 150));
+
+		// The deposit is fixed while challenged
+		assert_noop!(Tcr::deposit_more(Origin::signed(1), 0, 1, 50), Error::<Test, DefaultInstance>::ListingChallenged);
+	});
+}
+
+#[test]
+fn withdraw_excess_keeps_min_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 300, vec![]));
+
+		assert_noop!(
+			Tcr::withdraw_excess(Origin::signed(1), 0, 1, 201),
+			Error::<Test, DefaultInstance>::DepositTooLow
+		);
+		assert_noop!(
+			Tcr::withdraw_excess(Origin::signed(1), 0, 1, 301),
+			Error::<Test, DefaultInstance>::WithdrawalTooLarge
+		);
+		assert_ok!(Tcr::withdraw_excess(Origin::signed(1), 0, 1, 200));
+
+		assert_eq!(Tcr::listings(0, 1).deposit, 100);
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100);
+	});
+}
+
+#[test]
+fn withdraw_excess_blocked_while_challenged() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 300, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		assert_noop!(Tcr::withdraw_excess(Origin::signed(1), 0, 1, 100), Error::<Test, DefaultInstance>::ListingChallenged);
+	});
+}
+
+#[test]
+fn listing_data_is_stored() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![0; 17]),
+			Error::<Test, DefaultInstance>::ListingDataTooLong
+		);
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, b"ipfs://listing".to_vec()));
+		assert_eq!(Tcr::listing_data(0, 1), b"ipfs://listing".to_vec());
+
+		// The data goes away with the listing
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 200));
+		Tcr::on_finalize(20);
+		assert!(!<ListingData<Test>>::exists(0, 1));
+	});
+}
+
+#[test]
+fn content_addressed_listing_ids() {
+	new_test_ext().execute_with(|| {
+		CONTENT_ADDRESSED.with(|c| c.set(true));
+		let data = b"ipfs://listing".to_vec();
+		let id = TestListingIds::from_data_hash(BlakeTwo256::hash(&data)).unwrap();
+
+		// The id is derived on-chain, and an id given anyway must match the data
+		assert_noop!(
+			Tcr::propose(Origin::signed(1), 0, Some(id.wrapping_add(1)), 100, data.clone()),
+			Error::<Test, DefaultInstance>::ListingIdNotDerivedFromData
+		);
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, None, 100, data.clone()));
+		assert_eq!(Tcr::listing_data(0, id), data);
+
+		// Proposing the same content again collides
+		assert_noop!(Tcr::propose(Origin::signed(2), 0, Some(id), 100, data), Error::<Test, DefaultInstance>::ListingAlreadyExists);
+		CONTENT_ADDRESSED.with(|c| c.set(false));
+
+		// Without content addressing, proposers must pick an id
+		assert_noop!(
+			Tcr::propose(Origin::signed(1), 0, None, 100, vec![]),
+			Error::<Test, DefaultInstance>::ListingIdRequired
+		);
+	});
+}
+
+#[test]
+fn registry_parameters_have_lower_bounds() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Tcr::set_min_deposit(Origin::ROOT, 0, 9), Error::<Test, DefaultInstance>::MinDepositTooLow);
+		assert_noop!(Tcr::set_stage_lengths(Origin::ROOT, 0, 1, 0, 1), Error::<Test, DefaultInstance>::StageTooShort);
+
+		let params = RegistryParams { min_deposit: 0, apply_stage_len: 1, commit_stage_len: 1, reveal_stage_len: 1 };
+		assert_noop!(Tcr::create_registry(Origin::signed(2), params), Error::<Test, DefaultInstance>::MinDepositTooLow);
+		let params = RegistryParams { min_deposit: 10, apply_stage_len: 0, commit_stage_len: 1, reveal_stage_len: 1 };
+		assert_noop!(Tcr::create_registry(Origin::signed(2), params), Error::<Test, DefaultInstance>::StageTooShort);
+
+		// The bounds themselves are accepted
+		let params = RegistryParams { min_deposit: 10, apply_stage_len: 1, commit_stage_len: 1, reveal_stage_len: 1 };
+		assert_ok!(Tcr::create_registry(Origin::signed(2), params));
+	});
+}
+
+#[test]
+fn parameters_can_be_changed_by_param_origin() {
+	new_test_ext().execute_with(|| {
+		assert!(Tcr::set_min_deposit(Origin::signed(1), 0, 200).is_err());
+		assert!(Tcr::set_stage_lengths(Origin::signed(1), 0, 1, 2, 3).is_err());
+
+		assert_ok!(Tcr::set_min_deposit(Origin::ROOT, 0, 200));
+		assert_ok!(Tcr::set_stage_lengths(Origin::ROOT, 0, 1, 2, 3));
+		assert_eq!(Tcr::min_deposit(0), 200);
+		assert_eq!(Tcr::apply_stage_len(0), 1);
+		assert_eq!(Tcr::commit_stage_len(0), 2);
+		assert_eq!(Tcr::reveal_stage_len(0), 3);
+
+		// New proposals and challenges use the new parameters
+		assert_noop!(
+			Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]),
+			Error::<Test, DefaultInstance>::DepositTooLow
+		);
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 200, vec![]));
+		assert_eq!(Tcr::listings(0, 1).application_expiry, Some(1));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 200));
+		assert_eq!(Tcr::challenges(0).commit_end, 2);
+		assert_eq!(Tcr::challenges(0).reveal_end, 5);
+	});
+}
+
+#[test]
+fn ties_pass_at_half_threshold() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+
+		Tcr::on_finalize(20);
+
+		// Half of the vote is enough to keep the listing
+		assert!(Tcr::registry_contains(0, 1));
+	});
+}
+
+#[test]
+fn registered_listing_stays_without_quorum() {
+	new_test_ext().execute_with(|| {
+		VOTE_QUORUM.with(|v| v.set(500));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+
+		System::set_block_number(12);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 1), 400));
+		System::set_block_number(23);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(1)));
+
+		// Only 400 of the required 500 took part, so the listing stays
+		Tcr::on_finalize(32);
+		assert!(Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::listings(0, 1).challenge_id, None);
+
+		// Nobody loses anything
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100);
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Balances::free_balance(2), 1000_000);
+		assert_eq!(Balances::free_balance(3), 1000_000);
+		assert_eq!(Tcr::total_staked(3), 0);
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn challenged_application_is_registered_without_quorum() {
+	new_test_ext().execute_with(|| {
+		VOTE_QUORUM.with(|v| v.set(500));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		// Nobody votes, so the challenge cannot stop the application, whose apply stage is over
+		System::set_block_number(20);
+		Tcr::on_finalize(20);
+		assert!(Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::listings(0, 1).challenge_id, None);
+		assert_eq!(Tcr::applications_count(0), 0);
+		assert!(Tcr::challenge_outcomes(0).listing_kept);
+
+		// Nobody loses anything, and the owner's deposit stays staked
+		assert_eq!(Balances::free_balance(1), 1000_000 - 100);
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Balances::free_balance(2), 1000_000);
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn voided_challenges_do_not_cut_the_apply_stage_short() {
+	new_test_ext().execute_with(|| {
+		VOTE_QUORUM.with(|v| v.set(500));
+		assert_ok!(Tcr::set_stage_lengths(Origin::ROOT, 0, 50, 10, 10));
+
+		// The owner challenges their own application from another account, hoping nobody votes
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		System::set_block_number(20);
+		Tcr::on_finalize(20);
+
+		// The application resumes its apply stage instead of being accepted
+		assert!(Tcr::challenge_outcomes(0).listing_kept);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::listings(0, 1).application_expiry, Some(50));
+		assert_eq!(Tcr::pending_applications(0), vec![1]);
+
+		// so it can still be challenged by others
+		System::set_block_number(30);
+		assert_ok!(Tcr::challenge(Origin::signed(3), 0, 1, 300));
+		VOTE_QUORUM.with(|v| v.set(0));
+		System::set_block_number(50);
+		Tcr::on_finalize(50);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert!(Tcr::listing(0, 1).is_none());
+	});
+}
+
+#[test]
+fn failed_payouts_are_owed() {
+	new_test_ext().execute_with(|| {
+		// Events are not recorded in the genesis block
+		System::set_block_number(1);
+		VOTE_QUORUM.with(|v| v.set(500));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+
+		// The pot can no longer return the challenger's deposit when the challenge is voided
+		let _ = Balances::make_free_balance_be(&Tcr::account_id(), Balances::minimum_balance());
+		System::set_block_number(21);
+		Tcr::on_finalize(21);
+
+		assert!(System::events().iter().any(|record| record.event == TestEvent::tcr(RawEvent::PayoutFailed(2, 300))));
+		assert_eq!(Tcr::owed(2), 300);
+		assert_eq!(Tcr::total_staked(2), 0);
+
+		// The debt can only be claimed once the pot is topped up
+		assert!(Tcr::claim_owed(Origin::signed(2)).is_err());
+		assert_eq!(Tcr::owed(2), 300);
+		let _ = Balances::deposit_creating(&Tcr::account_id(), 100 + 300);
+		assert_ok!(Tcr::claim_owed(Origin::signed(2)));
+		assert!(System::events().iter().any(|record| record.event == TestEvent::tcr(RawEvent::OwedClaimed(2, 300))));
+		assert_eq!(Balances::free_balance(2), 1000_000);
+		assert_noop!(Tcr::claim_owed(Origin::signed(2)), Error::<Test, DefaultInstance>::NothingOwed);
+		assert_eq!(pot(), total_staked());
+	});
+}
+
+#[test]
+fn registry_queries_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		System::set_block_number(10);
+		Tcr::on_finalize(10);
+
+		// A registered listing being challenged, an application, and a challenged application
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(3), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(4), 100, vec![]));
+		System::set_block_number(11);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 4, 100));
+
+		assert_eq!(Tcr::pending_applications(0), vec![3]);
+		assert_eq!(Tcr::open_challenges(), vec![0, 1]);
+		assert_eq!(Tcr::listing(0, 2).map(|l| l.challenge_id), Some(Some(0)));
+		assert_eq!(Tcr::listing(0, 5), None);
+		assert_eq!(Tcr::challenge(1).map(|c| c.listing_id), Some(4));
+		assert_eq!(Tcr::challenge(2), None);
+
+		// The stale application expiry of listing 4 is left out
+		assert_eq!(
+			Tcr::upcoming_deadlines(11, 31),
+			vec![(20, 0, 2), (20, 0, 3), (21, 0, 4), (30, 0, 2), (31, 0, 4)]
+		);
+
+		// Wide ranges are cut short instead of scanning every block up to `to`
+		assert_eq!(
+			Tcr::upcoming_deadlines(11, u64::max_value()),
+			vec![(20, 0, 2), (20, 0, 3), (21, 0, 4), (30, 0, 2), (31, 0, 4)]
+		);
+
+		for block in 20..=30 {
+			System::set_block_number(block);
+			Tcr::on_finalize(block);
+		}
+		assert_eq!(Tcr::open_challenges(), vec![1]);
+		assert_eq!(Tcr::open_challenges_count(), 1);
+
+		System::set_block_number(31);
+		Tcr::on_finalize(31);
+		assert_eq!(Tcr::open_challenges(), Vec::<ChallengeId>::new());
+		assert_eq!(Tcr::pending_applications(0), Vec::<u32>::new());
+	});
+}
+
+#[test]
+fn deadline_queries_scan_a_bounded_range() {
+	new_test_ext().execute_with(|| {
+		let apply_stage_len = MAX_DEADLINE_RANGE as u64 + 10;
+		let params = RegistryParams { min_deposit: 100, apply_stage_len, commit_stage_len: 10, reveal_stage_len: 10 };
+		assert_ok!(Tcr::create_registry(Origin::signed(2), params));
+		assert_ok!(Tcr::propose(Origin::signed(1), 1, Some(1), 100, vec![]));
+		let expiry = Tcr::listings(1, 1).application_expiry.unwrap();
+
+		// The expiry is past the end of the widest range scanned from block 0
+		assert_eq!(Tcr::upcoming_deadlines(0, expiry), Vec::new());
+		assert_eq!(Tcr::upcoming_deadlines(expiry - 1, u64::max_value()), vec![(expiry, 1, 1)]);
+	});
+}
+
+#[test]
+fn registry_pages_through_registered_listings() {
+	new_test_ext().execute_with(|| {
+		for listing_id in 1..=5 {
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(listing_id), 100, vec![]));
+		}
+		System::set_block_number(10);
+		Tcr::on_finalize(10);
+
+		// Applications are not part of the registry
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(6), 100, vec![]));
+
+		let mut registered = Vec::new();
+		for page in 0..3 {
+			let listings = Tcr::registry(0, page, 2);
+			assert!(listings.len() <= 2);
+			registered.extend(listings.into_iter().map(|(listing_id, _)| listing_id));
+		}
+		registered.sort();
+		assert_eq!(registered, vec![1, 2, 3, 4, 5]);
+		assert!(Tcr::registry(0, 3, 2).is_empty());
+	});
+}
+
+#[test]
+fn pages_are_capped() {
+	new_test_ext().execute_with(|| {
+		let listings = MAX_PAGE_LIMIT + 1;
+		for listing_id in 1..=listings {
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(listing_id), 100, vec![]));
+		}
+
+		assert_eq!(Tcr::applications(0, 0, u32::max_value()).len(), MAX_PAGE_LIMIT as usize);
+		assert_eq!(Tcr::applications(0, 1, u32::max_value()).len(), 1);
+
+		// The settlement budget accepts 100 applications per block
+		for block in 10..12 {
+			System::set_block_number(block);
+			Tcr::on_finalize(block);
+		}
+		assert_eq!(Tcr::registry_count(0), listings);
+		assert_eq!(Tcr::registry(0, 0, u32::max_value()).len(), MAX_PAGE_LIMIT as usize);
+		assert_eq!(Tcr::registry(0, 1, u32::max_value()).len(), 1);
+	});
+}
+
+#[test]
+fn registry_indexes_follow_listings() {
+	new_test_ext().execute_with(|| {
+		for listing_id in 1..=3 {
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(listing_id), 100, vec![]));
+		}
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 3, 300));
+		assert_eq!(Tcr::applications_count(0), 3);
+		assert_eq!(Tcr::registry_count(0), 0);
+
+		// Promoted applications move to the registry, the challenged one stays an application
+		Tcr::on_finalize(10);
+		assert_eq!(Tcr::applications_count(0), 1);
+		assert_eq!(Tcr::application(0, 0), 3);
+		assert_eq!(Tcr::registry_count(0), 2);
+		assert_eq!(Tcr::applications(0, 0, 10).into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![3]);
+
+		// The rejected application is dropped from the index
+		Tcr::on_finalize(20);
+		assert_eq!(Tcr::applications_count(0), 0);
+		assert!(Tcr::applications(0, 0, 10).is_empty());
+
+		// Exiting moves the last registered listing into the freed position
+		System::set_block_number(21);
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+		Tcr::on_finalize(26);
+		assert_eq!(Tcr::registry_count(0), 1);
+		assert_eq!(Tcr::registry_listing(0, 0), 2);
+		assert_eq!(Tcr::registry(0, 0, 10).into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![2]);
+		assert_eq!(Tcr::registry(0, 1, 10), vec![]);
+	});
+}
+
+#[test]
+fn account_indexes_follow_stakes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 2, commitment(3, 0, false, 1), 100));
+
+		assert_eq!(Tcr::listings_of(&1), vec![(0, 1), (0, 2)]);
+		assert_eq!(Tcr::challenges_of(&2), vec![0]);
+		assert_eq!(Tcr::votes_of(&3).into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![0]);
+		assert_eq!(Tcr::total_staked(1), 200);
+		assert_eq!(pot(), total_staked());
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 2, false, salt(1)));
+		Tcr::on_finalize(20);
+
+		// The rejected application and the resolved challenge are dropped, the vote stays until claimed
+		assert_eq!(Tcr::listings_of(&1), vec![(0, 1)]);
+		assert_eq!(Tcr::challenges_of(&2), Vec::<ChallengeId>::new());
+		assert_eq!(Tcr::votes_of(&3).len(), 1);
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Tcr::total_staked(2), 0);
+
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert!(Tcr::votes_of(&3).is_empty());
+		assert_eq!(pot(), total_staked() + Tcr::pot_surplus());
+	});
+}
+
+#[test]
+fn resolved_challenges_are_pruned_and_archived() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 2, commitment(3, 1, true, 1), 100));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 2, true, salt(1)));
+		System::set_block_number(20);
+		Tcr::on_finalize(20);
+
+		// Without votes to claim, the challenge is dropped right away
+		assert_eq!(Tcr::challenge(0), None);
+		assert_eq!(Tcr::challenge_outcomes(0), ChallengeOutcome {
+			registry_id: 0,
+			listing_id: 1,
+			challenger: 2,
+			total_aye: 100,
+			total_nay: 300,
+			listing_kept: false,
+			quorum_met: true,
+			resolved_at: 20,
+		});
+
+		// Otherwise it is dropped with the last claim
+		assert!(Tcr::challenge(1).is_some());
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 1));
+		assert_eq!(Tcr::challenge(1), None);
+		assert!(!Tcr::claimed(1, 3));
+		assert_noop!(Tcr::claim_reward(Origin::signed(3), 1), Error::<Test, DefaultInstance>::ChallengeNotFound);
+		assert!(Tcr::challenge_outcomes(1).listing_kept);
+
+		// Outcomes are dropped after the retention period
+		Tcr::on_finalize(69);
+		assert!(<ChallengeOutcomes<Test>>::exists(0));
+		Tcr::on_finalize(70);
+		assert!(!<ChallengeOutcomes<Test>>::exists(0));
+		assert!(!<ChallengeOutcomes<Test>>::exists(1));
+	});
+}
+
+#[test]
+fn updates_over_budget_carry_over() {
+	new_test_ext().execute_with(|| {
+		for listing_id in 0..300 {
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(listing_id), 100, vec![]));
+		}
+		System::set_block_number(1);
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(300), 100, vec![]));
+
+		// 100 listings are promoted per block
+		System::set_block_number(10);
+		Tcr::on_finalize(10);
+		assert_eq!(Tcr::registry_count(0), 100);
+		assert_eq!(Tcr::update_cursor(), Some((10, 100)));
+		assert_eq!(Tcr::finalize_weight(), weights::SCHEDULE + 100 * weights::update());
+
+		System::set_block_number(11);
+		Tcr::on_finalize(11);
+		assert_eq!(Tcr::registry_count(0), 200);
+		assert_eq!(Tcr::update_cursor(), Some((10, 200)));
+
+		// The backlog is cleared first, then later blocks are caught up with
+		System::set_block_number(12);
+		Tcr::on_finalize(12);
+		assert_eq!(Tcr::registry_count(0), 300);
+		assert!(!Tcr::registry_contains(0, 300));
+		assert!(!<ListingsToUpdate<Test>>::exists(10));
+		assert_eq!(Tcr::update_cursor(), Some((11, 0)));
+
+		System::set_block_number(13);
+		Tcr::on_finalize(13);
+		assert_eq!(Tcr::registry_count(0), 301);
+		assert_eq!(Tcr::update_cursor(), None);
+		assert_eq!(Tcr::finalize_weight(), 3 * weights::SCHEDULE + weights::update());
+		assert!(!<ListingsToUpdate<Test>>::exists(11));
+	});
+}
+
+#[test]
+fn held_back_updates_apply_as_of_their_due_block() {
+	new_test_ext().execute_with(|| {
+		SETTLEMENT_BUDGET.with(|b| b.set(weights::update()));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 300));
+
+		// One update per block
+		System::set_block_number(10);
+		Tcr::on_finalize(10);
+		assert!(Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::update_cursor(), Some((10, 1)));
+
+		for block in 20..=21 {
+			System::set_block_number(block);
+			Tcr::on_finalize(block);
+		}
+		assert!(<Listings<Test>>::exists(0, 2));
+
+		// The challenge ending at block 20 is settled late
+		System::set_block_number(22);
+		Tcr::on_finalize(22);
+		assert!(!<Listings<Test>>::exists(0, 2));
+		assert_eq!(Tcr::challenge_outcomes(0).resolved_at, 22);
+		assert_eq!(Balances::free_balance(2), 1000_000 + 100);
+
+		System::set_block_number(23);
+		Tcr::on_finalize(23);
+		assert_eq!(Tcr::update_cursor(), None);
+	});
+}
+
+#[test]
+fn expired_applications_are_promoted_before_being_challenged() {
+	new_test_ext().execute_with(|| {
+		SETTLEMENT_BUDGET.with(|b| b.set(weights::update()));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+
+		// Only the first promotion fits in the budget
+		System::set_block_number(10);
+		Tcr::on_finalize(10);
+		assert!(!Tcr::registry_contains(0, 2));
+
+		// The overdue promotion is not lost to the challenge
+		System::set_block_number(11);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+		assert!(Tcr::registry_contains(0, 2));
+		assert_eq!(Tcr::listings(0, 2).challenge_id, Some(0));
+		assert_eq!(Tcr::applications_count(0), 0);
+		assert_eq!(Tcr::registry_count(0), 2);
+
+		// The held back update is now a no-op, and the challenge runs its course
+		Tcr::on_finalize(11);
+		assert_eq!(Tcr::registry_count(0), 2);
+		assert_eq!(Tcr::listings(0, 2).challenge_id, Some(0));
+		assert_eq!(Tcr::challenges(0).reveal_end, 31);
+	});
+}
+
+#[test]
+fn update_status_promotes_expired_applications() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+
+		System::set_block_number(10);
+		assert_noop!(Tcr::update_status(Origin::signed(3), 0, 1), Error::<Test, DefaultInstance>::NothingToUpdate);
+		assert_noop!(Tcr::update_status(Origin::signed(3), 0, 2), Error::<Test, DefaultInstance>::ListingNotFound);
+
+		// The application expired without on_finalize promoting it
+		System::set_block_number(11);
+		assert_ok!(Tcr::update_status(Origin::signed(3), 0, 1));
+		assert!(Tcr::registry_contains(0, 1));
+		assert_noop!(Tcr::update_status(Origin::signed(3), 0, 1), Error::<Test, DefaultInstance>::NothingToUpdate);
+
+		// No tip without a surplus in the pot, and the scheduled update is now a no-op
+		assert_eq!(Balances::free_balance(3), 1000_000);
+		Tcr::on_finalize(11);
+		assert_eq!(Tcr::registry_count(0), 1);
+	});
+}
+
+#[test]
+fn update_status_settles_expired_challenges_for_a_tip() {
+	new_test_ext().execute_with(|| {
+		SETTLEMENT_BUDGET.with(|b| b.set(weights::update()));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 100));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(1)));
+		assert_noop!(Tcr::update_status(Origin::signed(3), 0, 2), Error::<Test, DefaultInstance>::NothingToUpdate);
+
+		// Only the first challenge fits in the budget
+		System::set_block_number(20);
+		Tcr::on_finalize(20);
+		assert!(Tcr::listing(0, 2).unwrap().challenge_id.is_some());
+
+		// The losing voter's stake became surplus when the first challenge was settled, which pays for the tip
+		System::set_block_number(21);
+		assert_eq!(Tcr::pot_surplus(), 100);
+		assert_ok!(Tcr::update_status(Origin::signed(4), 0, 2));
+		assert!(Tcr::registry_contains(0, 2));
+		assert_eq!(Tcr::challenge_outcomes(1).listing_kept, true);
+		assert_eq!(Balances::free_balance(4), 1000_000 - 100 + 10);
+		assert_eq!(Tcr::pot_surplus(), 90);
+
+		// The winning voter can still claim the whole reward pool
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_eq!(Balances::free_balance(3), 1000_000 + 150);
+		assert_eq!(pot(), 100 + 100 + 90);
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn split_slash_pays_for_tips_with_the_node_configuration() {
+	new_test_ext().execute_with(|| {
+		SLASH_TO.with(|v| v.set(SlashTo::Split));
+		SETTLEMENT_BUDGET.with(|b| b.set(weights::update()));
+
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 100));
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(1)));
+		System::set_block_number(20);
+		Tcr::on_finalize(20);
+
+		// The tip share of the losing voter's stake stays in the pot, the rest goes to the treasury
+		System::set_block_number(21);
+		assert_eq!(Tcr::pot_surplus(), 10);
+		assert_eq!(Balances::free_balance(TREASURY), 90);
+
+		// which pays for settling the challenge left over by on_finalize
+		assert_ok!(Tcr::update_status(Origin::signed(3), 0, 2));
+		assert_eq!(Balances::free_balance(3), 1000_000 - 400 + 10);
+		assert_eq!(Tcr::pot_surplus(), 0);
+		assert_ok!(Tcr::sweep_vote(Origin::signed(3), 0, 4));
+		assert_eq!(pot(), total_staked() + Tcr::challenges(0).reward_pool);
+	});
+}
+
+#[test]
+fn votes_are_stored_by_voter() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 2), 100));
+
+		// A repeat vote is rejected, whatever its side or stake
+		assert_noop!(
+			Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 3), 1000),
+			Error::<Test, DefaultInstance>::AlreadyVoted
+		);
+		assert_eq!(Tcr::challenges(0).vote_count, 2);
+		assert_eq!(Tcr::votes(0, 3), Vote { commitment: commitment(3, 0, true, 1), aye_or_nay: None, deposit: 400 });
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_eq!(Tcr::votes(0, 3).aye_or_nay, Some(true));
+		assert_eq!(Tcr::challenges(0).total_aye, 100 + 400);
+
+		// Claimed votes are removed. Only the winning vote is left to claim, the unrevealed one is forfeited.
+		Tcr::on_finalize(20);
+		assert_eq!(Tcr::challenges(0).vote_count, 1);
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert!(!<Votes<Test>>::exists(0, 3));
+		assert!(!<Challenges<Test>>::exists(0));
+	});
+}
+
+#[test]
+fn resolved_challenges_do_not_wait_on_forfeited_votes() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(2), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 2, 100));
+
+		// Votes cannot be free
+		assert_noop!(
+			Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 9),
+			Error::<Test, DefaultInstance>::VoteStakeTooLow
+		);
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 10));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 2, commitment(4, 1, false, 2), 10));
+
+		// Voter 4 never reveals
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		Tcr::on_finalize(20);
+
+		// Without a vote left to claim, the second challenge is dropped right away
+		assert!(Tcr::registry_contains(0, 2));
+		assert!(!<Challenges<Test>>::exists(1));
+
+		// The first one is dropped with the winning vote
+		assert_eq!(Tcr::challenges(0).vote_count, 1);
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert!(!<Challenges<Test>>::exists(0));
+
+		// and the forfeited votes can still be swept afterwards
+		assert_eq!(Tcr::votes_of(&4).len(), 2);
+		assert_ok!(Tcr::sweep_vote(Origin::signed(1), 0, 4));
+		assert_ok!(Tcr::sweep_vote(Origin::signed(1), 1, 4));
+		assert!(Tcr::votes_of(&4).is_empty());
+		assert_eq!(Tcr::total_staked(4), 0);
+		assert_eq!(pot(), total_staked() + Tcr::pot_surplus());
+	});
+}
+
+#[test]
+fn votes_can_be_changed_or_withdrawn_during_commit_stage() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_noop!(Tcr::withdraw_vote(Origin::signed(3), 0, 1), Error::<Test, DefaultInstance>::NotChallenged);
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, true, 2), 100));
+		assert_noop!(Tcr::change_vote(Origin::signed(1), 0, 1, commitment(1, 0, false, 1)), Error::<Test, DefaultInstance>::VoteNotFound);
+
+		// Voter 3 changes sides, voter 4 withdraws
+		assert_ok!(Tcr::change_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 3)));
+		assert_ok!(Tcr::withdraw_vote(Origin::signed(4), 0, 1));
+		assert_eq!(Tcr::total_staked(4), 0);
+		assert!(Tcr::votes_of(&4).is_empty());
+		assert_eq!(Tcr::challenges(0).vote_count, 1);
+		assert_noop!(Tcr::withdraw_vote(Origin::signed(4), 0, 1), Error::<Test, DefaultInstance>::VoteNotFound);
+
+		// Neither is possible once the commit stage is over
+		System::set_block_number(11);
+		assert_noop!(Tcr::change_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1)), Error::<Test, DefaultInstance>::CommitStageEnded);
+		assert_noop!(Tcr::withdraw_vote(Origin::signed(3), 0, 1), Error::<Test, DefaultInstance>::CommitStageEnded);
+		assert_noop!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)), Error::<Test, DefaultInstance>::CommitmentMismatch);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, false, salt(3)));
+		assert_eq!(Tcr::challenges(0).total_aye, 100);
+		assert_eq!(Tcr::challenges(0).total_nay, 100 + 400);
+
+		Tcr::on_finalize(20);
+		assert!(!Tcr::registry_contains(0, 1));
+		assert_noop!(Tcr::claim_reward(Origin::signed(4), 0), Error::<Test, DefaultInstance>::VoteNotFound);
+	});
+}
+
+#[test]
+fn votes_cannot_be_changed_during_reveal_stage() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 100));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+
+		// Once revealing starts, a change would let the voter follow the tallies
+		System::set_block_number(11);
+		assert_noop!(Tcr::change_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 2)), Error::<Test, DefaultInstance>::CommitStageEnded);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_noop!(Tcr::change_vote(Origin::signed(3), 0, 1, commitment(3, 0, false, 2)), Error::<Test, DefaultInstance>::CommitStageEnded);
+		assert_eq!(Tcr::challenges(0).total_aye, 100 + 400);
+		assert_eq!(Tcr::challenges(0).total_nay, 100);
+	});
+}
+
+#[test]
+fn pot_holds_every_outstanding_stake() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+		assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+		assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 100));
+		assert_eq!(pot(), total_staked());
+		assert_eq!(pot(), 100 + 300 + 400 + 100);
+
+		// Stakes leave the staker's account alive
+		assert_noop!(
+			Tcr::propose(Origin::signed(1), 0, Some(2), 1000_000 - 100, vec![]),
+			Error::<Test, DefaultInstance>::InsufficientBalance
+		);
+
+		System::set_block_number(11);
+		assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+		assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(1)));
+
+		// The forfeited challenger deposit is split between the owner and the reward pool, and the
+		// losing stake is slashed to the surplus. It stays in the voter's total until the vote is dropped.
+		Tcr::on_finalize(20);
+		assert_eq!(Tcr::total_staked(2), 0);
+		assert_eq!(pot(), total_staked() - Tcr::total_staked(4) + Tcr::challenges(0).reward_pool + Tcr::pot_surplus());
+
+		// Once all votes are claimed, only the listing deposit and the forfeited stake remain
+		assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+		assert_ok!(Tcr::claim_reward(Origin::signed(4), 0));
+		assert_eq!(total_staked(), 100);
+		assert_eq!(pot(), total_staked() + Tcr::pot_surplus());
+
+		// Exiting returns the last stake
+		assert_ok!(Tcr::exit(Origin::signed(1), 0, 1));
+		Tcr::on_finalize(16);
+		assert_eq!(pot(), Tcr::pot_surplus());
+		assert_eq!(total_balance(), 4 * 1000_000);
+	});
+}
+
+#[test]
+fn forfeited_stakes_go_to_the_slash_handler() {
+	for &slash_to in &[SlashTo::Pot, SlashTo::Treasury, SlashTo::Burn, SlashTo::Split] {
+		new_test_ext().execute_with(|| {
+			SLASH_TO.with(|v| v.set(slash_to));
+
+			assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+			assert_ok!(Tcr::challenge(Origin::signed(2), 0, 1, 300));
+			assert_ok!(Tcr::commit_vote(Origin::signed(3), 0, 1, commitment(3, 0, true, 1), 400));
+			assert_ok!(Tcr::commit_vote(Origin::signed(4), 0, 1, commitment(4, 0, false, 1), 100));
+
+			System::set_block_number(11);
+			assert_ok!(Tcr::reveal_vote(Origin::signed(3), 0, 1, true, salt(1)));
+			assert_ok!(Tcr::reveal_vote(Origin::signed(4), 0, 1, false, salt(1)));
+			assert_noop!(Tcr::sweep_vote(Origin::signed(1), 0, 4), Error::<Test, DefaultInstance>::ChallengeNotResolved);
+			Tcr::on_finalize(20);
+
+			// The losing voter's stake leaves the pot when the challenge is settled, whatever the
+			// handler does with it, and anyone can then drop the vote
+			assert_eq!(pot(), total_staked() - 100 + Tcr::challenges(0).reward_pool + Tcr::pot_surplus());
+			assert_noop!(Tcr::sweep_vote(Origin::signed(1), 0, 3), Error::<Test, DefaultInstance>::VoteNotForfeited);
+			assert_ok!(Tcr::sweep_vote(Origin::signed(1), 0, 4));
+			assert_noop!(Tcr::sweep_vote(Origin::signed(1), 0, 4), Error::<Test, DefaultInstance>::VoteNotFound);
+			assert!(Tcr::votes_of(&4).is_empty());
+			assert_eq!(Tcr::total_staked(4), 0);
+			assert_eq!(Balances::free_balance(4), 1000_000 - 100);
+
+			assert_ok!(Tcr::claim_reward(Origin::signed(3), 0));
+			assert_eq!(Tcr::challenge(0), None);
+			assert_eq!(pot(), total_staked() + Tcr::pot_surplus());
+
+			let issuance = 4 * 1000_000 + 3 * Balances::minimum_balance();
+			match slash_to {
+				SlashTo::Pot => {
+					assert_eq!(Tcr::pot_surplus(), 100);
+					assert_eq!(Balances::total_issuance(), issuance);
+				}
+				SlashTo::Treasury => {
+					assert_eq!(Tcr::pot_surplus(), 0);
+					assert_eq!(Balances::free_balance(TREASURY), 100);
+					assert_eq!(Balances::total_issuance(), issuance);
+				}
+				SlashTo::Burn => {
+					assert_eq!(Tcr::pot_surplus(), 0);
+					assert_eq!(Balances::total_issuance(), issuance - 100);
+				}
+				SlashTo::Split => {
+					assert_eq!(Tcr::pot_surplus(), 10);
+					assert_eq!(Balances::free_balance(TREASURY), 90);
+					assert_eq!(Balances::total_issuance(), issuance);
+				}
+			}
+		});
+	}
+}
+
+#[test]
+fn registries_are_independent() {
+	new_test_ext().execute_with(|| {
+		let data = b"token".to_vec();
+		let token = BlakeTwo256::hash(&data);
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+
+		// Each registry has its own listing ids and parameters
+		assert_noop!(
+			Tokens::propose(Origin::signed(1), 0, Some(H256::zero()), 50, data.clone()),
+			Error::<Test, Instance1>::ListingIdNotDerivedFromData
+		);
+		assert_ok!(Tokens::propose(Origin::signed(1), 0, None, 50, data));
+
+		// and its own pot
+		assert_ne!(Tcr::account_id(), Tokens::account_id());
+		assert_eq!(pot(), 100);
+		assert_eq!(Balances::free_balance(Tokens::account_id()), Balances::minimum_balance() + 50);
+		assert_eq!(Tcr::total_staked(1), 100);
+		assert_eq!(Tokens::total_staked(1), 50);
+
+		// The token registry's shorter apply stage ends first
+		Tcr::on_finalize(5);
+		Tokens::on_finalize(5);
+		assert!(Tokens::registry_contains(0, token));
+		assert!(!Tcr::registry_contains(0, 1));
+		assert_eq!(Tcr::applications_count(0), 1);
+	});
+}
+
+#[test]
+fn created_registries_have_their_own_parameters_and_listings() {
+	new_test_ext().execute_with(|| {
+		let params = RegistryParams { min_deposit: 200, apply_stage_len: 3, commit_stage_len: 4, reveal_stage_len: 5 };
+		assert_noop!(Tcr::propose(Origin::signed(1), 1, Some(1), 200, vec![]), Error::<Test, DefaultInstance>::RegistryNotFound);
+		assert_noop!(Tcr::set_min_deposit(Origin::ROOT, 1, 200), Error::<Test, DefaultInstance>::RegistryNotFound);
+
+		// The creator stakes the registry deposit
+		assert_ok!(Tcr::create_registry(Origin::signed(2), params));
+		assert_eq!(Tcr::next_registry_id(), 2);
+		assert_eq!(Tcr::total_staked(2), 500);
+		assert_eq!(pot(), 500);
+		assert_eq!(Tcr::min_deposit(1), 200);
+		assert_eq!(Tcr::min_deposit(0), 100);
+
+		// The same listing id can be proposed in both registries, with each registry's parameters
+		assert_ok!(Tcr::propose(Origin::signed(1), 0, Some(1), 100, vec![]));
+		assert_noop!(Tcr::propose(Origin::signed(1), 1, Some(1), 100, vec![]), Error::<Test, DefaultInstance>::DepositTooLow);
+		assert_ok!(Tcr::propose(Origin::signed(1), 1, Some(1), 200, vec![]));
+		assert_eq!(Tcr::listings(1, 1).application_expiry, Some(3));
+		assert_eq!(Tcr::listings_of(&1), vec![(0, 1), (1, 1)]);
+
+		System::set_block_number(3);
+		Tcr::on_finalize(3);
+		assert!(Tcr::registry_contains(1, 1));
+		assert!(!Tcr::registry_contains(0, 1));
+
+		// Challenges of either listing are numbered together
+		assert_ok!(Tcr::challenge(Origin::signed(3), 1, 1, 200));
+		assert_ok!(Tcr::challenge(Origin::signed(3), 0, 1, 100));
+		assert_eq!(Tcr::challenge(0).map(|c| c.registry_id), Some(1));
+		assert_eq!(Tcr::challenge(1).map(|c| c.registry_id), Some(0));
+		assert_eq!(Tcr::challenges(0).reveal_end, 3 + 4 + 5);
+	});
+}
+
+#[test]
+fn registered_accounts_pass_membership_checks() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Members::propose(Origin::signed(1), GENESIS_REGISTRY, Some(3), 100, vec![]));
+		assert_ok!(Members::propose(Origin::signed(1), GENESIS_REGISTRY, Some(2), 100, vec![]));
+
+		// Applicants are not members yet
+		assert!(!Members::contains(&3));
+		assert!(EnsureRegistered::<Test, Instance2>::ensure_origin(Origin::signed(3)).is_err());
+
+		System::set_block_number(10);
+		Members::on_finalize(10);
+		assert!(Members::contains(&3));
+		assert_eq!(Members::sorted_members(), vec![2, 3]);
+		assert_eq!(EnsureRegistered::<Test, Instance2>::ensure_origin(Origin::signed(3)).ok(), Some(3));
+		assert!(EnsureRegistered::<Test, Instance2>::ensure_origin(Origin::signed(4)).is_err());
+		assert!(EnsureRegistered::<Test, Instance2>::ensure_origin(Origin::ROOT).is_err());
+
+		// Members lose access once they exit, and listings in other registries do not count
+		assert_ok!(Members::exit(Origin::signed(1), GENESIS_REGISTRY, 3));
+		assert_ok!(Members::create_registry(Origin::signed(4), RegistryParams {
+			min_deposit: 100,
+			apply_stage_len: 5,
+			commit_stage_len: 10,
+			reveal_stage_len: 10,
+		}));
+		assert_ok!(Members::propose(Origin::signed(1), 1, Some(4), 100, vec![]));
+		Members::on_finalize(15);
+		assert!(!Members::contains(&3));
+		assert!(Members::registry_contains(1, 4));
+		assert!(!Members::contains(&4));
+		assert_eq!(Members::sorted_members(), vec![2]);
 	});
 }
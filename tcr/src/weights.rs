@@ -0,0 +1,97 @@
+//! Weights of the TCR calls and of the status updates applied in `on_finalize`, as linear
+//! functions of their components. The coefficients are to be fitted to the benchmarks in
+//! `benchmarking.rs`, and refreshed whenever the calls change. They have not been fitted yet:
+//! the values below are estimates, to be replaced with the results of a benchmark run on
+//! reference hardware.
+
+use frame_support::weights::Weight;
+
+/// Reading the status updates scheduled for a block in `on_finalize`.
+pub const SCHEDULE: Weight = 1_000;
+
+/// `create_registry`.
+pub fn create_registry() -> Weight {
+	30_000
+}
+
+/// `set_min_deposit`.
+pub fn set_min_deposit() -> Weight {
+	5_000
+}
+
+/// `set_stage_lengths`.
+pub fn set_stage_lengths() -> Weight {
+	5_000
+}
+
+/// `propose`, with `d` bytes of listing data.
+pub fn propose(d: u32) -> Weight {
+	40_000u32.saturating_add(d.saturating_mul(10))
+}
+
+/// `challenge`, priced as the more expensive of `challenge_application` and `challenge_listing`.
+pub fn challenge() -> Weight {
+	50_000
+}
+
+/// `commit_vote`.
+pub fn commit_vote() -> Weight {
+	30_000
+}
+
+/// `reveal_vote`.
+pub fn reveal_vote() -> Weight {
+	30_000
+}
+
+/// `change_vote`.
+pub fn change_vote() -> Weight {
+	20_000
+}
+
+/// `withdraw_vote`.
+pub fn withdraw_vote() -> Weight {
+	30_000
+}
+
+/// `deposit_more`.
+pub fn deposit_more() -> Weight {
+	20_000
+}
+
+/// `withdraw_excess`.
+pub fn withdraw_excess() -> Weight {
+	20_000
+}
+
+/// `exit`, priced as the more expensive of `exit` and `exit_immediately`, as which one applies
+/// depends on `ExitDelay`.
+pub fn exit() -> Weight {
+	30_000
+}
+
+/// `claim_reward`.
+pub fn claim_reward() -> Weight {
+	40_000
+}
+
+/// `sweep_vote`.
+pub fn sweep_vote() -> Weight {
+	30_000
+}
+
+/// `claim_owed`.
+pub fn claim_owed() -> Weight {
+	20_000
+}
+
+/// `update_status`, when it settles a challenge, which is the more expensive case.
+pub fn update_status() -> Weight {
+	20_000u32.saturating_add(update())
+}
+
+/// A status update in `on_finalize`, settling a challenge if there is one.
+/// Votes are claimed separately, so this does not depend on the number of votes.
+pub fn update() -> Weight {
+	10_000
+}